@@ -0,0 +1,141 @@
+//! JSON-RPC interface of the music styles pallet
+//!
+//! The methods query the `MusicStylesApi` runtime API, at the best block unless a block hash is
+//! given, and return the styles names as UTF-8 strings.
+
+#[cfg(test)]
+mod tests;
+
+use allfeat_support::types::music::style::MusicStyleName;
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject},
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::{marker::PhantomData, sync::Arc};
+
+pub use pallet_music_styles::runtime_api::MusicStylesApi as MusicStylesRuntimeApi;
+
+/// A parent style along with its sub styles
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    pub name: String,
+    pub sub_styles: Vec<String>,
+}
+
+/// A style matching a search, `parent` is set for the sub styles
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleMatch {
+    pub name: String,
+    pub parent: Option<String>,
+}
+
+#[rpc(client, server)]
+pub trait MusicStylesApi<BlockHash> {
+    /// All the styles of the tree
+    #[method(name = "musicStyles_list")]
+    fn list(&self, at: Option<BlockHash>) -> RpcResult<Vec<Style>>;
+
+    /// Sub styles of a parent style, `null` if the parent style doesn't exist
+    #[method(name = "musicStyles_children")]
+    fn children(&self, parent: String, at: Option<BlockHash>) -> RpcResult<Option<Vec<String>>>;
+
+    /// Styles whose name contains the query, ignoring the case
+    #[method(name = "musicStyles_search")]
+    fn search(&self, query: String, at: Option<BlockHash>) -> RpcResult<Vec<StyleMatch>>;
+}
+
+/// Error code returned when the runtime API call fails
+const RUNTIME_ERROR: i32 = 1;
+
+/// Implementation of the music styles JSON-RPC methods
+pub struct MusicStyles<C, Block> {
+    client: Arc<C>,
+    _marker: PhantomData<Block>,
+}
+
+impl<C, Block> MusicStyles<C, Block> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn to_string(name: &MusicStyleName) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+fn runtime_error(error: impl ToString) -> jsonrpsee::core::Error {
+    CallError::Custom(ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Unable to query the music styles.",
+        Some(error.to_string()),
+    ))
+    .into()
+}
+
+impl<C, Block> MusicStylesApiServer<<Block as BlockT>::Hash> for MusicStyles<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: MusicStylesRuntimeApi<Block>,
+{
+    fn list(&self, at: Option<Block::Hash>) -> RpcResult<Vec<Style>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let styles = self
+            .client
+            .runtime_api()
+            .all_styles(at)
+            .map_err(runtime_error)?;
+
+        Ok(styles
+            .iter()
+            .map(|(name, subs)| Style {
+                name: to_string(name),
+                sub_styles: subs.iter().map(to_string).collect(),
+            })
+            .collect())
+    }
+
+    fn children(&self, parent: String, at: Option<Block::Hash>) -> RpcResult<Option<Vec<String>>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let children = self
+            .client
+            .runtime_api()
+            .children(at, parent.into_bytes())
+            .map_err(runtime_error)?;
+
+        Ok(children.map(|subs| subs.iter().map(to_string).collect()))
+    }
+
+    fn search(&self, query: String, at: Option<Block::Hash>) -> RpcResult<Vec<StyleMatch>> {
+        let query = query.to_lowercase();
+        let matches = |name: &str| name.to_lowercase().contains(&query);
+
+        let mut found = Vec::new();
+        for style in self.list(at)? {
+            if matches(&style.name) {
+                found.push(StyleMatch {
+                    name: style.name.clone(),
+                    parent: None,
+                });
+            }
+            for sub in style.sub_styles.into_iter().filter(|sub| matches(sub)) {
+                found.push(StyleMatch {
+                    name: sub,
+                    parent: Some(style.name.clone()),
+                });
+            }
+        }
+
+        Ok(found)
+    }
+}
\ No newline at end of file
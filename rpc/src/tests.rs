@@ -0,0 +1,217 @@
+use super::*;
+use allfeat_support::types::music::style::MusicStyleDB;
+use pallet_music_styles::StyleRef;
+use sp_api::ApiRef;
+use sp_blockchain::{BlockStatus, Info};
+use sp_core::H256;
+use sp_runtime::testing::{Block as TestBlock, ExtrinsicWrapper, Header};
+
+type Block = TestBlock<ExtrinsicWrapper<u64>>;
+
+fn bounded(name: &[u8]) -> MusicStyleName {
+    name.to_vec().try_into().unwrap()
+}
+
+/// Genesis styles of the pallet mock: Raggae [], Rap [Drill, Trap, Hardcore], Rock [Hardcore]
+fn styles() -> MusicStyleDB {
+    let mut styles = MusicStyleDB::default();
+    let entries: [(&[u8], &[&[u8]]); 3] = [
+        (b"Raggae", &[]),
+        (b"Rap", &[b"Drill", b"Trap", b"Hardcore"]),
+        (b"Rock", &[b"Hardcore"]),
+    ];
+    for (name, subs) in entries {
+        let subs = subs.iter().map(|sub| bounded(sub)).collect::<Vec<_>>();
+        styles
+            .try_insert(bounded(name), subs.try_into().unwrap())
+            .unwrap();
+    }
+    styles
+}
+
+struct TestApi {
+    styles: MusicStyleDB,
+}
+
+struct TestRuntimeApi {
+    styles: MusicStyleDB,
+}
+
+impl TestRuntimeApi {
+    fn find(&self, name: Vec<u8>) -> Option<StyleRef> {
+        let name: MusicStyleName = name.try_into().ok()?;
+        if self.styles.contains_key(&name) {
+            return Some(StyleRef::Parent(name));
+        }
+        self.styles
+            .iter()
+            .find(|(_, subs)| subs.contains(&name))
+            .map(|(parent, _)| StyleRef::Sub(parent.clone(), name))
+    }
+}
+
+impl ProvideRuntimeApi<Block> for TestApi {
+    type Api = TestRuntimeApi;
+
+    fn runtime_api(&self) -> ApiRef<'_, Self::Api> {
+        TestRuntimeApi {
+            styles: self.styles.clone(),
+        }
+        .into()
+    }
+}
+
+impl HeaderBackend<Block> for TestApi {
+    fn header(&self, _hash: H256) -> sp_blockchain::Result<Option<Header>> {
+        Ok(None)
+    }
+
+    fn info(&self) -> Info<Block> {
+        Info {
+            best_hash: Default::default(),
+            best_number: 0,
+            genesis_hash: Default::default(),
+            finalized_hash: Default::default(),
+            finalized_number: 0,
+            finalized_state: None,
+            number_leaves: 0,
+            block_gap: None,
+        }
+    }
+
+    fn status(&self, _hash: H256) -> sp_blockchain::Result<BlockStatus> {
+        Ok(BlockStatus::Unknown)
+    }
+
+    fn number(&self, _hash: H256) -> sp_blockchain::Result<Option<u64>> {
+        Ok(None)
+    }
+
+    fn hash(&self, _number: u64) -> sp_blockchain::Result<Option<<Block as BlockT>::Hash>> {
+        Ok(None)
+    }
+}
+
+sp_api::mock_impl_runtime_apis! {
+    impl MusicStylesRuntimeApi<Block> for TestRuntimeApi {
+        fn all_styles(&self) -> MusicStyleDB {
+            self.styles.clone()
+        }
+
+        fn children(&self, parent: Vec<u8>) -> Option<Vec<MusicStyleName>> {
+            let parent: MusicStyleName = parent.try_into().ok()?;
+            self.styles.get(&parent).map(|subs| subs.to_vec())
+        }
+
+        fn resolve(&self, name: Vec<u8>) -> Option<StyleRef> {
+            self.find(name)
+        }
+
+        fn exists(&self, name: Vec<u8>) -> bool {
+            self.find(name).is_some()
+        }
+
+        fn path(&self, name: Vec<u8>) -> Option<Vec<MusicStyleName>> {
+            self.find(name).map(|style| match style {
+                StyleRef::Parent(name) => vec![name],
+                StyleRef::Sub(parent, name) => vec![parent, name],
+            })
+        }
+    }
+}
+
+fn new_rpc() -> MusicStyles<TestApi, Block> {
+    MusicStyles::new(Arc::new(TestApi { styles: styles() }))
+}
+
+#[test]
+fn list_returns_readable_styles() {
+    let styles = new_rpc().list(None).unwrap();
+
+    assert_eq!(
+        styles,
+        vec![
+            Style {
+                name: "Raggae".into(),
+                sub_styles: vec![],
+            },
+            Style {
+                name: "Rap".into(),
+                sub_styles: vec!["Drill".into(), "Trap".into(), "Hardcore".into()],
+            },
+            Style {
+                name: "Rock".into(),
+                sub_styles: vec!["Hardcore".into()],
+            },
+        ]
+    );
+}
+
+#[test]
+fn list_accepts_a_block_hash() {
+    let styles = new_rpc().list(Some(H256::repeat_byte(1))).unwrap();
+
+    assert_eq!(styles.len(), 3);
+}
+
+#[test]
+fn children_of_a_parent_style() {
+    let rpc = new_rpc();
+
+    assert_eq!(
+        rpc.children("Rock".into(), None).unwrap(),
+        Some(vec!["Hardcore".to_string()])
+    );
+    assert_eq!(rpc.children("Raggae".into(), None).unwrap(), Some(vec![]));
+    assert_eq!(rpc.children("Jazz".into(), None).unwrap(), None);
+}
+
+#[test]
+fn search_ignores_the_case() {
+    let rpc = new_rpc();
+
+    assert_eq!(
+        rpc.search("HARD".into(), None).unwrap(),
+        vec![
+            StyleMatch {
+                name: "Hardcore".into(),
+                parent: Some("Rap".into()),
+            },
+            StyleMatch {
+                name: "Hardcore".into(),
+                parent: Some("Rock".into()),
+            },
+        ]
+    );
+    assert_eq!(
+        rpc.search("ra".into(), None).unwrap(),
+        vec![
+            StyleMatch {
+                name: "Raggae".into(),
+                parent: None,
+            },
+            StyleMatch {
+                name: "Rap".into(),
+                parent: None,
+            },
+            StyleMatch {
+                name: "Trap".into(),
+                parent: Some("Rap".into()),
+            },
+        ]
+    );
+    assert!(rpc.search("jazz".into(), None).unwrap().is_empty());
+}
+
+#[test]
+fn style_serializes_to_camel_case() {
+    let style = Style {
+        name: "Rock".into(),
+        sub_styles: vec!["Hardcore".into()],
+    };
+
+    assert_eq!(
+        serde_json::to_string(&style).unwrap(),
+        r#"{"name":"Rock","subStyles":["Hardcore"]}"#
+    );
+}
\ No newline at end of file
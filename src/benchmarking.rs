@@ -4,10 +4,36 @@ use super::*;
 
 #[allow(unused)]
 use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
-use frame_support::traits::UnfilteredDispatchable;
+use frame_support::traits::{Hooks, UnfilteredDispatchable};
 
-fn assert_last_event<T: Config>(generic_event: <T as Config>::RuntimeEvent) {
-    frame_system::Pallet::<T>::assert_last_event(generic_event.into());
+/// Fill the storage with `p` parent styles holding `x` sub styles each, the sub style `0x61` is
+/// held by every parent style
+fn fill_styles<T: Config>(p: u32, x: u32) {
+    let mut styles = MusicStyleDB::new();
+    for i in 0..p {
+        let mut subs: MusicSubStyles = Default::default();
+        for j in 0..x {
+            let name = if j == 0 {
+                vec![0x61]
+            } else {
+                vec![0x62, j as u8]
+            };
+            subs.try_push(name.try_into().unwrap()).unwrap();
+        }
+        styles
+            .try_insert(i.to_le_bytes().to_vec().try_into().unwrap(), subs)
+            .unwrap();
+    }
+    Pallet::<T>::put_styles(styles);
+}
+
+/// A reason of the maximum length, to measure the worst case of the calls accepting one
+fn max_reason() -> Option<ChangeReason> {
+    Some(
+        vec![0x61; <MaxReasonLength as Get<u32>>::get() as usize]
+            .try_into()
+            .unwrap(),
+    )
 }
 
 benchmarks! {
@@ -30,17 +56,11 @@ benchmarks! {
         }
 
         let origin = T::AdminOrigin::try_successful_origin();
-        let call = Call::<T>::add_style { name: new_style.clone(), sub: new_sub_styles.clone() };
+        let call = Call::<T>::add_style { name: new_style.clone(), sub: new_sub_styles.clone(), reason: max_reason() };
     }: { call.dispatch_bypass_filter(origin.unwrap())? }
     verify {
-        if let Some(sub_styles) = new_sub_styles {
-            assert_last_event::<T>(
-                Event::<T>::SubStyleAdded(sub_styles.last().unwrap().clone()).into()
-            );
-        }
-        else {
-            assert_last_event::<T>(Event::<T>::StyleAdded(new_style).into());
-        }
+        assert!(ParentStyles::<T>::contains_key(MusicStyleName::try_from(new_style).unwrap()));
+        assert_eq!(TaxonomyVersion::<T>::get(), x as u64 + 1);
     }
 
     add_sub_style {
@@ -54,12 +74,339 @@ benchmarks! {
         }
 
         let origin = T::AdminOrigin::try_successful_origin();
-        Call::<T>::add_style { name: parent_style.clone(), sub: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
-        let call = Call::<T>::add_sub_style { parent_style, subs_style: new_subs_style.clone() };
+        Call::<T>::add_style { name: parent_style.clone(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::add_sub_style { parent_style, subs_style: new_subs_style.clone(), reason: max_reason() };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        let last_sub = MusicStyleName::try_from(new_subs_style.last().unwrap().clone()).unwrap();
+        assert!(SubStyleIndex::<T>::iter_key_prefix(last_sub).next().is_some());
+    }
+
+    update_style_name {
+        let origin = T::AdminOrigin::try_successful_origin();
+        Call::<T>::add_style { name: b"Rap".to_vec(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::update_style_name {
+            parent_style: b"Rap".to_vec(),
+            sub_style: None,
+            new_name: b"Hip-Hop".to_vec(),
+            reason: max_reason(),
+        };
     }: { call.dispatch_bypass_filter(origin.unwrap())? }
     verify {
-        assert_last_event::<T>(Event::<T>::SubStyleAdded(new_subs_style.last().unwrap().clone()).into());
+        assert!(ScheduledChanges::<T>::contains_key(0));
+    }
+
+    remove_style {
+        let origin = T::AdminOrigin::try_successful_origin();
+        Call::<T>::add_style { name: b"Rap".to_vec(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::remove_style { parent_style: b"Rap".to_vec(), sub_style: None, reason: max_reason() };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(ScheduledChanges::<T>::contains_key(0));
+    }
+
+    cancel_change {
+        let origin = T::AdminOrigin::try_successful_origin();
+        Call::<T>::add_style { name: b"Rap".to_vec(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        Call::<T>::remove_style { parent_style: b"Rap".to_vec(), sub_style: None, reason: None }
+            .dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::cancel_change { id: 0 };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(!ScheduledChanges::<T>::contains_key(0));
+    }
+
+    enact_changes {
+        let n in 0..T::MaxScheduledPerBlock::get();
+
+        let origin = T::AdminOrigin::try_successful_origin();
+        for i in 0..n {
+            let name = vec![0x61, i as u8];
+            Call::<T>::add_style { name: name.clone(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+            Call::<T>::update_style_name {
+                parent_style: name,
+                sub_style: None,
+                new_name: vec![0x62, i as u8],
+                reason: max_reason(),
+            }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        }
+        let when = frame_system::Pallet::<T>::block_number().saturating_add(T::EnactmentDelay::get());
+    }: { Pallet::<T>::on_initialize(when) }
+    verify {
+        assert_eq!(ScheduledChanges::<T>::iter().count(), 0);
+    }
+
+    defer_changes {
+        let n in 0..T::MaxScheduledPerBlock::get();
+
+        let origin = T::AdminOrigin::try_successful_origin();
+        for i in 0..n {
+            let name = vec![0x61, i as u8];
+            Call::<T>::add_style { name: name.clone(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+            Call::<T>::update_style_name {
+                parent_style: name,
+                sub_style: None,
+                new_name: vec![0x62, i as u8],
+                reason: max_reason(),
+            }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        }
+        Paused::<T>::put(true);
+        let when = frame_system::Pallet::<T>::block_number().saturating_add(T::EnactmentDelay::get());
+    }: { Pallet::<T>::on_initialize(when) }
+    verify {
+        assert_eq!(DeferredRange::<T>::get(), (0, n));
+    }
+
+    set_frozen {
+        let origin = T::AdminOrigin::try_successful_origin();
+        let call = Call::<T>::set_frozen { frozen: true };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(Pallet::<T>::is_frozen());
+    }
+
+    set_style_lock {
+        let origin = T::AdminOrigin::try_successful_origin();
+        Call::<T>::add_style { name: b"Rap".to_vec(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::set_style_lock { parent_style: b"Rap".to_vec(), sub_style: None, locked: true };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(LockedStyles::<T>::contains_key(StyleRef::Parent(b"Rap".to_vec().try_into().unwrap())));
+    }
+
+    pause {
+        let origin = T::PauseOrigin::try_successful_origin();
+        let call = Call::<T>::pause {};
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(Pallet::<T>::is_paused());
+    }
+
+    unpause {
+        let origin = T::PauseOrigin::try_successful_origin();
+        Call::<T>::pause {}.dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::unpause {};
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(!Pallet::<T>::is_paused());
+    }
+
+    set_style_status {
+        let origin = T::AdminOrigin::try_successful_origin();
+        Call::<T>::add_style { name: b"Rap".to_vec(), sub: None, reason: None }.dispatch_bypass_filter(origin.clone().unwrap())?;
+        let call = Call::<T>::set_style_status { name: b"Rap".to_vec(), status: StyleStatus::Retired, reason: max_reason() };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert_eq!(Pallet::<T>::status_of(&b"Rap".to_vec().try_into().unwrap()), StyleStatus::Retired);
+    }
+
+    // Revert the addition of `n` sub styles, each one is removed from the tree
+    revert_changes {
+        let n in 1..<MaxSubStyles as Get<u32>>::get();
+
+        let subs: Vec<Vec<u8>> = (0..n).map(|i| vec![0x62, i as u8]).collect();
+        Call::<T>::add_style { name: b"Rap".to_vec(), sub: Some(subs), reason: None }
+            .dispatch_bypass_filter(T::AdminOrigin::try_successful_origin().unwrap())?;
+        let origin = T::TaxonomyOrigin::try_successful_origin();
+        let call = Call::<T>::revert_changes { count: n, reason: max_reason() };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert_eq!(ParentStyles::<T>::get(MusicStyleName::try_from(b"Rap".to_vec()).unwrap()).unwrap().len(), 0);
+        assert_eq!(TaxonomyVersion::<T>::get(), 2 * n as u64 + 1);
+    }
+
+    // Hash a styles tree of `p` full parent styles
+    refresh_taxonomy_hash {
+        let p in 0..<MaxParentStyles as Get<u32>>::get();
+
+        fill_styles::<T>(p, <MaxSubStyles as Get<u32>>::get());
+        TaxonomyHash::<T>::kill();
+    }: { Pallet::<T>::refresh_taxonomy_hash() }
+    verify {
+        assert_eq!(TaxonomyHash::<T>::get(), Some(Pallet::<T>::hash_styles()));
+    }
+
+    // Replace `p` full parent styles by a parent style holding `n` sub styles
+    set_taxonomy {
+        let n in 1..<MaxSubStyles as Get<u32>>::get();
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+
+        fill_styles::<T>(p, <MaxSubStyles as Get<u32>>::get());
+        let subs: Vec<MusicStyleName> = (0..n).map(|i| vec![0x63, i as u8].try_into().unwrap()).collect();
+        let mut new = MusicStyleDB::new();
+        new.try_insert(b"Jazz".to_vec().try_into().unwrap(), BoundedVec::truncate_from(subs)).unwrap();
+        let origin = T::TaxonomyOrigin::try_successful_origin();
+        let call = Call::<T>::set_taxonomy { new: new.clone(), reason: max_reason() };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert_eq!(Pallet::<T>::get_styles(), new);
+    }
+
+    // Queue `c` chunks, the first one holding a parent style with `n - 1` sub styles
+    queue_import {
+        let c in 1..<MaxImportChunks as Get<u32>>::get();
+        let n in 1..<MaxSubStyles as Get<u32>>::get() + 1;
+
+        let mut import = Vec::new();
+        for i in 0..c {
+            let subs: Vec<MusicStyleName> = if i == 0 {
+                (1..n).map(|j| vec![0x63, j as u8].try_into().unwrap()).collect()
+            } else {
+                Vec::new()
+            };
+            let mut chunk = MusicStyleDB::new();
+            chunk.try_insert(i.to_le_bytes().to_vec().try_into().unwrap(), BoundedVec::truncate_from(subs)).unwrap();
+            import.push(chunk);
+        }
+        let origin = T::TaxonomyOrigin::try_successful_origin();
+        let call = Call::<T>::queue_import { chunks: BoundedVec::truncate_from(import), reason: max_reason() };
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert_eq!(Import::<T>::get().unwrap().total(), c);
+    }
+
+    // Stage a chunk holding a parent style with `n` sub styles, already staged by a previous chunk
+    // along with another sub style
+    stage_import {
+        let n in 1..<MaxSubStyles as Get<u32>>::get() - 1;
+
+        let mut import = Vec::new();
+        for subs in [0..1, 1..n + 1] {
+            let subs: Vec<MusicStyleName> = subs.map(|j| vec![0x63, j as u8].try_into().unwrap()).collect();
+            let mut chunk = MusicStyleDB::new();
+            chunk.try_insert(b"Jazz".to_vec().try_into().unwrap(), BoundedVec::truncate_from(subs)).unwrap();
+            import.push(chunk);
+        }
+        Pallet::<T>::do_queue_import(BoundedVec::truncate_from(import), None, None)?;
+        Pallet::<T>::stage_chunk(QueuedChunks::<T>::take(0).unwrap())?;
+    }: { Pallet::<T>::stage_chunk(QueuedChunks::<T>::take(1).unwrap())? }
+    verify {
+        let name: MusicStyleName = b"Jazz".to_vec().try_into().unwrap();
+        assert_eq!(StagedStyles::<T>::get(name).unwrap().len(), n as usize + 1);
+    }
+
+    // Cancel an import of `c` chunks still queued and `p` staged parent styles
+    cancel_import {
+        let c in 0..<MaxImportChunks as Get<u32>>::get() - 1;
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+
+        let mut chunk = MusicStyleDB::new();
+        for i in 0..p {
+            chunk.try_insert(i.to_le_bytes().to_vec().try_into().unwrap(), Default::default()).unwrap();
+        }
+        let import = vec![chunk; c as usize + 1];
+        Pallet::<T>::do_queue_import(BoundedVec::truncate_from(import), None, None)?;
+        Pallet::<T>::stage_chunk(QueuedChunks::<T>::take(0).unwrap())?;
+        let origin = T::TaxonomyOrigin::try_successful_origin();
+        let call = Call::<T>::cancel_import {};
+    }: { call.dispatch_bypass_filter(origin.unwrap())? }
+    verify {
+        assert!(Import::<T>::get().is_none());
+    }
+
+    // Compute the diffs between `p` full parent styles and a staged parent style holding `n - 1`
+    // sub styles
+    swap_import {
+        let n in 1..<MaxSubStyles as Get<u32>>::get() + 1;
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+
+        fill_styles::<T>(p, <MaxSubStyles as Get<u32>>::get());
+        let subs: Vec<MusicStyleName> = (1..n).map(|j| vec![0x63, j as u8].try_into().unwrap()).collect();
+        let mut chunk = MusicStyleDB::new();
+        chunk.try_insert(b"Jazz".to_vec().try_into().unwrap(), BoundedVec::truncate_from(subs)).unwrap();
+        Pallet::<T>::do_queue_import(BoundedVec::truncate_from(vec![chunk.clone()]), None, None)?;
+        Pallet::<T>::stage_chunk(QueuedChunks::<T>::take(0).unwrap())?;
+    }: {
+        let mut staged = MusicStyleDB::new();
+        for (parent, subs) in StagedStyles::<T>::iter() {
+            staged.try_insert(parent, subs).unwrap();
+        }
+        Pallet::<T>::taxonomy_diffs(Pallet::<T>::get_styles(), &staged)?;
+    }
+    verify {
+        let diffs = Pallet::<T>::taxonomy_diffs(Pallet::<T>::get_styles(), &chunk)?;
+        assert_eq!(diffs.len(), (p + n) as usize);
+    }
+
+    // Apply and record the removal of a full parent style, once per diff of an import
+    apply_import_diff {
+        fill_styles::<T>(1, <MaxSubStyles as Get<u32>>::get());
+        let name: MusicStyleName = 0u32.to_le_bytes().to_vec().try_into().unwrap();
+        let diff = TaxonomyDiff::RemoveParent { subs: ParentStyles::<T>::get(&name).unwrap(), name };
+    }: {
+        Pallet::<T>::put_diff(&diff)?;
+        Pallet::<T>::note_applied(diff, None, max_reason());
+    }
+    verify {
+        assert_eq!(ParentStyles::<T>::count(), 0);
+    }
+
+    // Resume a lazy migration of the styles tree stopped by an undecodable parent style
+    resume_styles_migration {
+        StylesMigration::<T>::put(migrations::v1::MigrationCursor { offset: 0, remaining: 2, step: 1, failed: true });
+        let call = Call::<T>::resume_styles_migration { offset: 1, remaining: 1 };
+    }: { call.dispatch_bypass_filter(frame_system::RawOrigin::Root.into())? }
+    verify {
+        assert!(!StylesMigration::<T>::get().unwrap().failed);
+    }
+
+    parent_styles {
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+        fill_styles::<T>(p, 0);
+    }: { <Pallet<T> as InspectMusicStyles>::parent_styles() }
+    verify {
+        assert_eq!(<Pallet<T> as InspectMusicStyles>::parent_styles().len(), p as usize);
+    }
+
+    sub_styles {
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+        fill_styles::<T>(p, <MaxSubStyles as Get<u32>>::get());
+    }: { <Pallet<T> as InspectMusicStyles>::sub_styles() }
+    verify {
+        assert_eq!(
+            <Pallet<T> as InspectMusicStyles>::sub_styles().len(),
+            (p * <MaxSubStyles as Get<u32>>::get()) as usize
+        );
+    }
+
+    is_parent_style {
+        fill_styles::<T>(1, 0);
+        let name: MusicStyleName = 0u32.to_le_bytes().to_vec().try_into().unwrap();
+    }: { <Pallet<T> as InspectMusicStyles>::is_parent_style(&name) }
+    verify {
+        assert!(<Pallet<T> as InspectMusicStyles>::is_parent_style(&name));
+    }
+
+    // Worst case, every parent style holds the sub style and all but the last are drafts
+    is_sub_style {
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+        fill_styles::<T>(p, 1);
+        for i in 0..p - 1 {
+            StyleStatuses::<T>::insert(
+                MusicStyleName::try_from(i.to_le_bytes().to_vec()).unwrap(),
+                StyleStatus::Draft,
+            );
+        }
+        let name: MusicStyleName = vec![0x61].try_into().unwrap();
+    }: { <Pallet<T> as InspectMusicStyles>::is_sub_style(&name) }
+    verify {
+        assert!(<Pallet<T> as InspectMusicStyles>::is_sub_style(&name));
+    }
+
+    exist {
+        let p in 1..<MaxParentStyles as Get<u32>>::get();
+        fill_styles::<T>(p, 1);
+        for i in 0..p - 1 {
+            StyleStatuses::<T>::insert(
+                MusicStyleName::try_from(i.to_le_bytes().to_vec()).unwrap(),
+                StyleStatus::Draft,
+            );
+        }
+        let name: MusicStyleName = vec![0x61].try_into().unwrap();
+    }: { <Pallet<T> as InspectMusicStyles>::exist(&name) }
+    verify {
+        assert!(<Pallet<T> as InspectMusicStyles>::exist(&name));
     }
 
     impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(false), crate::mock::Test);
-}
+}
\ No newline at end of file
@@ -0,0 +1,315 @@
+//! Changes turning a styles tree into another one
+//!
+//! The diff looks for the fewest changes: a sub style leaving a parent style for another one is
+//! moved, and a style is renamed rather than removed and added again when its new name looks like
+//! the old one, or for a parent style when it keeps at least half of its sub styles. A parent style
+//! keeping fewer of them is still renamed when the tree has no room left to add the new one before
+//! removing it. The order of the sub styles isn't part of the diff.
+
+use super::*;
+
+/// Sub styles leaving a parent style of the old tree
+struct Leaving<'a> {
+    /// Name of the parent style in the old tree
+    parent: &'a MusicStyleName,
+    /// Name of the parent style in the new tree, `None` if it is removed
+    new_parent: Option<&'a MusicStyleName>,
+    subs: Vec<&'a MusicStyleName>,
+}
+
+impl<'a> Leaving<'a> {
+    /// Name of the parent style once the parent styles are renamed
+    fn current_parent(&self) -> &'a MusicStyleName {
+        self.new_parent.unwrap_or(self.parent)
+    }
+}
+
+/// Sub style moved from a parent style of the old tree to another one
+struct Moving<'a> {
+    /// Name of the parent style in the old tree
+    parent: &'a MusicStyleName,
+    /// Name of the parent style once the parent styles are renamed
+    current_parent: &'a MusicStyleName,
+    sub: &'a MusicStyleName,
+    new_parent: &'a MusicStyleName,
+}
+
+impl<'a> Moving<'a> {
+    fn change(&self) -> StyleChange {
+        StyleChange::Move {
+            style: StyleRef::Sub(self.current_parent.clone(), self.sub.clone()),
+            new_parent: self.new_parent.clone(),
+        }
+    }
+}
+
+/// Changes turning the `old` styles tree into the `new` one, in an order in which they can be
+/// applied one after the other
+///
+/// The changes never need more parent styles than [`MaxParentStyles`]: every removal and every
+/// move between parent styles of the old tree come before any addition. The removals of parent
+/// styles come first, then the removals of sub styles, the renames of parent styles and of sub
+/// styles and the moves between parent styles of the old tree, a full parent style giving sub
+/// styles away before receiving any. The parent styles which sub styles are moved out of are
+/// removed after the moves. The only exception are the sub styles moved into an added parent
+/// style: the parent style is added right before them and the parent styles they leave are
+/// removed right after them, within the room left by the new tree. The sub styles are added last.
+pub fn diff(old: &MusicStyleDB, new: &MusicStyleDB) -> Vec<StyleChange> {
+    let mut added_parents: Vec<&MusicStyleName> =
+        new.keys().filter(|name| !old.contains_key(*name)).collect();
+
+    // Room left for the parent styles removed once their sub styles are moved into added ones
+    let mut room = (<MaxParentStyles as Get<u32>>::get() as usize).saturating_sub(new.len());
+
+    // Parent styles of the old tree along with their name in the new tree
+    let mut parents: Vec<(&MusicStyleName, Option<&MusicStyleName>)> = Vec::new();
+    for (name, subs) in old.iter() {
+        if new.contains_key(name) {
+            parents.push((name, Some(name)));
+            continue;
+        }
+
+        let most_kept = added_parents
+            .iter()
+            .enumerate()
+            .map(|(i, new_name)| (i, kept(subs, &new[*new_name])))
+            .filter(|(_, kept)| *kept > 0)
+            .max_by_key(|(i, kept)| (*kept, sp_std::cmp::Reverse(*i)));
+        let renamed = added_parents
+            .iter()
+            .position(|new_name| similar(name, new_name))
+            .or_else(|| {
+                most_kept
+                    .filter(|(_, kept)| kept * 2 >= subs.len())
+                    .map(|(i, _)| i)
+            })
+            .or_else(|| {
+                let (i, _) = most_kept?;
+                if room == 0 {
+                    return Some(i);
+                }
+                room -= 1;
+                None
+            });
+        parents.push((name, renamed.map(|i| added_parents.remove(i))));
+    }
+
+    let mut leaving: Vec<Leaving> = parents
+        .iter()
+        .map(|(parent, new_parent)| Leaving {
+            parent,
+            new_parent: *new_parent,
+            subs: old[*parent]
+                .iter()
+                .filter(|sub| !new_parent.is_some_and(|new_parent| new[new_parent].contains(sub)))
+                .collect(),
+        })
+        .collect();
+
+    // Sub styles joining each parent style of the new tree
+    let mut joining: Vec<(&MusicStyleName, Vec<&MusicStyleName>)> = new
+        .iter()
+        .map(|(name, new_subs)| {
+            let old_subs = parents
+                .iter()
+                .find(|(_, new_parent)| *new_parent == Some(name))
+                .map(|(parent, _)| &old[*parent]);
+            let joined = new_subs
+                .iter()
+                .filter(|sub| !old_subs.is_some_and(|subs| subs.contains(sub)))
+                .collect();
+            (name, joined)
+        })
+        .collect();
+
+    // A sub style leaving a parent style and joining another one is moved
+    let mut moves = Vec::new();
+    for (new_parent, joined) in joining.iter_mut() {
+        joined.retain(|sub| {
+            let from = leaving
+                .iter_mut()
+                .find(|from| from.new_parent != Some(*new_parent) && from.subs.contains(sub));
+            let Some(from) = from else {
+                return true;
+            };
+
+            from.subs.retain(|left| left != sub);
+            moves.push(Moving {
+                parent: from.parent,
+                current_parent: from.current_parent(),
+                sub: *sub,
+                new_parent: *new_parent,
+            });
+            false
+        });
+    }
+
+    // A sub style leaving a parent style while a similar one joins it is renamed
+    let mut renamed_subs = Vec::new();
+    for from in leaving.iter_mut() {
+        let Some(new_parent) = from.new_parent else {
+            continue;
+        };
+        let Some((_, joined)) = joining.iter_mut().find(|(name, _)| *name == new_parent) else {
+            continue;
+        };
+
+        from.subs.retain(|sub| {
+            let Some(i) = joined.iter().position(|new_name| similar(sub, new_name)) else {
+                return true;
+            };
+
+            renamed_subs.push(StyleChange::Rename {
+                style: StyleRef::Sub(new_parent.clone(), (*sub).clone()),
+                new_name: joined.remove(i).clone(),
+            });
+            false
+        });
+    }
+
+    // Number of sub styles of each parent style of the old tree once the sub styles are removed
+    let mut sizes: Vec<(&MusicStyleName, usize)> = leaving
+        .iter()
+        .map(|from| {
+            let removed = if from.new_parent.is_some() {
+                from.subs.len()
+            } else {
+                0
+            };
+            (from.current_parent(), old[from.parent].len() - removed)
+        })
+        .collect();
+
+    // The moves between parent styles of the old tree go first, a parent style making room before
+    // receiving sub styles when it is full
+    let (mut inner_moves, mut outer_moves): (Vec<Moving>, Vec<Moving>) = moves
+        .into_iter()
+        .partition(|moving| !added_parents.contains(&moving.new_parent));
+    let max_subs = <MaxSubStyles as Get<u32>>::get() as usize;
+    let mut ordered_moves = Vec::with_capacity(inner_moves.len());
+    while !inner_moves.is_empty() {
+        let has_room = |name: &MusicStyleName| {
+            sizes
+                .iter()
+                .any(|(parent, size)| *parent == name && *size < max_subs)
+        };
+        let i = inner_moves
+            .iter()
+            .position(|moving| has_room(moving.new_parent))
+            .unwrap_or(0);
+        let moving = inner_moves.remove(i);
+        for (parent, size) in sizes.iter_mut() {
+            if *parent == moving.current_parent {
+                *size = size.saturating_sub(1);
+            } else if *parent == moving.new_parent {
+                *size += 1;
+            }
+        }
+        ordered_moves.push(moving);
+    }
+
+    // The parent styles which sub styles are moved out of are removed after the moves
+    let removed_parents = leaving
+        .iter()
+        .filter(|from| from.new_parent.is_none())
+        .map(|from| from.parent);
+    let moved_out = |moves: &[Moving], parent: &MusicStyleName| {
+        moves.iter().any(|moving| moving.parent == parent)
+    };
+    let (mut late_removals, early_removals): (Vec<&MusicStyleName>, Vec<&MusicStyleName>) =
+        removed_parents.partition(|parent| moved_out(&outer_moves, *parent));
+    let (middle_removals, early_removals): (Vec<&MusicStyleName>, Vec<&MusicStyleName>) =
+        early_removals
+            .into_iter()
+            .partition(|parent| moved_out(&ordered_moves, *parent));
+    let remove_parent = |name: &MusicStyleName| StyleChange::Remove {
+        style: StyleRef::Parent(name.clone()),
+        force: false,
+    };
+
+    let mut changes: Vec<StyleChange> = early_removals.into_iter().map(remove_parent).collect();
+    // The sub styles are removed before their parent style is renamed
+    for from in leaving.iter().filter(|from| from.new_parent.is_some()) {
+        changes.extend(from.subs.iter().map(|sub| StyleChange::Remove {
+            style: StyleRef::Sub(from.parent.clone(), (*sub).clone()),
+            force: false,
+        }));
+    }
+    for (parent, new_parent) in parents {
+        match new_parent {
+            Some(new_parent) if parent != new_parent => changes.push(StyleChange::Rename {
+                style: StyleRef::Parent(parent.clone()),
+                new_name: new_parent.clone(),
+            }),
+            _ => {}
+        }
+    }
+    changes.extend(renamed_subs);
+    changes.extend(ordered_moves.iter().map(Moving::change));
+    changes.extend(middle_removals.into_iter().map(remove_parent));
+    for name in added_parents {
+        changes.push(StyleChange::Add {
+            style: StyleRef::Parent(name.clone()),
+        });
+        changes.extend(
+            outer_moves
+                .iter()
+                .filter(|moving| moving.new_parent == name)
+                .map(Moving::change),
+        );
+        outer_moves.retain(|moving| moving.new_parent != name);
+        late_removals.retain(|parent| {
+            if moved_out(&outer_moves, *parent) {
+                return true;
+            }
+            changes.push(remove_parent(*parent));
+            false
+        });
+    }
+    for (parent, joined) in joining {
+        changes.extend(joined.into_iter().map(|sub| StyleChange::Add {
+            style: StyleRef::Sub(parent.clone(), sub.clone()),
+        }));
+    }
+
+    changes
+}
+
+/// Number of sub styles of the old parent style kept by the new one
+fn kept(old_subs: &MusicSubStyles, new_subs: &MusicSubStyles) -> usize {
+    old_subs.iter().filter(|sub| new_subs.contains(sub)).count()
+}
+
+/// Whether two names most likely are the same style: they only differ by their case and
+/// punctuation, or by a typo every five characters
+fn similar(a: &[u8], b: &[u8]) -> bool {
+    let normalize = |name: &[u8]| -> Vec<u8> {
+        name.iter()
+            .filter(|c| !c.is_ascii() || c.is_ascii_alphanumeric())
+            .map(u8::to_ascii_lowercase)
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+
+    edit_distance(&a, &b) <= a.len().max(b.len()) / 5
+}
+
+/// Levenshtein distance between two byte strings
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, x) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        sp_std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
\ No newline at end of file
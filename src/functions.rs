@@ -1,7 +1,91 @@
 use super::*;
-use allfeat_support::types::music::style::MusicSubStyles;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use sp_std::collections::btree_set::BTreeSet;
 
 impl<T: Config> Pallet<T> {
+    /// The whole styles tree, including the draft styles
+    pub fn get_styles() -> MusicStyleDB {
+        let mut styles = MusicStyleDB::new();
+        for (parent, subs) in <ParentStyles<T>>::iter() {
+            // The number of parent styles is bounded when they are written
+            let _ = styles.try_insert(parent, subs);
+        }
+        styles
+    }
+
+    /// Write the styles tree, only the parent styles whose sub styles changed are written
+    pub(super) fn put_styles(styles: MusicStyleDB) {
+        Self::write_styles(&Self::get_styles(), &styles);
+    }
+
+    /// Turn the stored parent styles of `current` into the ones of `styles`, the other parent
+    /// styles are left untouched
+    fn write_styles(current: &MusicStyleDB, styles: &MusicStyleDB) {
+        for (parent, subs) in current.iter() {
+            if styles.get(parent) != Some(subs) {
+                for sub in subs.iter() {
+                    <SubStyleIndex<T>>::remove(sub, parent);
+                }
+                <ParentStyles<T>>::remove(parent);
+            }
+        }
+
+        for (parent, subs) in styles.iter() {
+            if current.get(parent) != Some(subs) {
+                for sub in subs.iter() {
+                    <SubStyleIndex<T>>::insert(sub, parent, ());
+                }
+                <ParentStyles<T>>::insert(parent, subs);
+            }
+        }
+    }
+
+    /// Part of the stored styles tree holding the given parent styles, the other parent styles
+    /// are not read
+    pub(super) fn styles_of<'a>(
+        parents: impl IntoIterator<Item = &'a MusicStyleName>,
+    ) -> MusicStyleDB {
+        let mut styles = MusicStyleDB::new();
+        for parent in parents {
+            if let Some(subs) = <ParentStyles<T>>::get(parent) {
+                // A change only involves a couple of parent styles
+                let _ = styles.try_insert(parent.clone(), subs);
+            }
+        }
+        styles
+    }
+
+    /// Whether the name is used by a parent style or a sub style, including the draft styles
+    pub(super) fn name_exists(name: &MusicStyleName) -> bool {
+        <ParentStyles<T>>::contains_key(name)
+            || <SubStyleIndex<T>>::iter_key_prefix(name).next().is_some()
+    }
+
+    /// Whether a visible parent style has the given name
+    pub(super) fn has_parent_style(name: &MusicStyleName) -> bool {
+        <ParentStyles<T>>::contains_key(name) && Self::status_of(name).is_visible()
+    }
+
+    /// Visible parent styles holding the given visible sub style
+    pub(super) fn parents_of(name: &MusicStyleName) -> Vec<MusicStyleName> {
+        if !Self::status_of(name).is_visible() {
+            return Vec::new();
+        }
+
+        let mut parents: Vec<MusicStyleName> = <SubStyleIndex<T>>::iter_key_prefix(name)
+            .filter(|parent| Self::status_of(parent).is_visible())
+            .collect();
+        parents.sort();
+        parents
+    }
+
+    /// Whether a visible sub style of a visible parent style has the given name
+    pub(super) fn has_sub_style(name: &MusicStyleName) -> bool {
+        Self::status_of(name).is_visible()
+            && <SubStyleIndex<T>>::iter_key_prefix(name)
+                .any(|parent| Self::status_of(&parent).is_visible())
+    }
+
     pub(super) fn to_bounded_style(value: Vec<u8>) -> Result<MusicStyleName, DispatchError> {
         Ok(value.try_into().map_err(|_| Error::<T>::NameTooLong)?)
     }
@@ -17,31 +101,1173 @@ impl<T: Config> Pallet<T> {
         Ok(subs)
     }
 
+    /// Append new sub styles to the given ones, rejecting the names already used
     pub(super) fn checked_add_subs(
-        tree: &mut MusicStyleDB,
-        subs: MusicSubStyles,
-        into: MusicStyleName,
+        subs: &mut MusicSubStyles,
+        new_subs: &MusicSubStyles,
     ) -> DispatchResult {
-        match tree.get_mut(&into) {
-            Some(s) => {
-                for sub in subs.iter() {
-                    if s.contains(sub) {
-                        return Err(Error::<T>::NameAlreadyExists)?;
-                    } else {
-                        s.try_push(sub.clone())
-                            .map_err(|_| Error::<T>::StylesCapacity)?;
+        for sub in new_subs.iter() {
+            ensure!(!subs.contains(sub), Error::<T>::NameAlreadyExists);
+            subs.try_push(sub.clone())
+                .map_err(|_| Error::<T>::StylesCapacity)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a new parent style along with its sub styles, emit the events and notify the dependent
+    /// pallets. Shared by the extrinsics and the `MutateMusicStyles` implementation
+    pub(super) fn do_add_style(
+        name: MusicStyleName,
+        new_subs: MusicSubStyles,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        Self::ensure_not_paused()?;
+        Self::ensure_not_frozen()?;
+        Self::add_style(name, new_subs, true, who, reason)
+    }
+
+    /// Add a new parent style along with its sub styles, the new styles count towards the rate
+    /// limits unless they are added by a governance bulk change
+    fn add_style(
+        name: MusicStyleName,
+        new_subs: MusicSubStyles,
+        rate_limited: bool,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        ensure!(
+            !<ParentStyles<T>>::contains_key(&name),
+            Error::<T>::NameAlreadyExists
+        );
+        ensure!(
+            <ParentStyles<T>>::count() < MaxParentStyles::get(),
+            Error::<T>::StylesCapacity
+        );
+
+        let mut subs = MusicSubStyles::default();
+        Self::checked_add_subs(&mut subs, &new_subs)?;
+        if rate_limited {
+            Self::note_new_styles(1, new_subs.len() as u32)?;
+        }
+
+        <ParentStyles<T>>::insert(&name, subs);
+        Self::note_diff(
+            TaxonomyDiff::AddParent {
+                name: name.clone(),
+                subs: Default::default(),
+            },
+            who.clone(),
+            reason.clone(),
+        );
+
+        Self::note_added_subs(&name, 0, new_subs, who, reason);
+
+        Ok(())
+    }
+
+    /// Add sub styles to an existing parent style, emit the events and notify the dependent
+    /// pallets. Shared by the extrinsics and the `MutateMusicStyles` implementation
+    pub(super) fn do_add_sub_styles(
+        parent: MusicStyleName,
+        new_subs: MusicSubStyles,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        Self::ensure_not_paused()?;
+        Self::ensure_not_frozen()?;
+
+        let mut subs = <ParentStyles<T>>::get(&parent).ok_or(Error::<T>::StyleNotFound)?;
+        let first_index = subs.len() as u32;
+        Self::checked_add_subs(&mut subs, &new_subs)?;
+        Self::note_new_styles(0, new_subs.len() as u32)?;
+
+        <ParentStyles<T>>::insert(&parent, subs);
+        Self::note_added_subs(&parent, first_index, new_subs, who, reason);
+
+        Ok(())
+    }
+
+    /// Index the sub styles added to a parent style from the given position, record them in the
+    /// history and the change log, emit the events and notify the dependent pallets
+    fn note_added_subs(
+        parent: &MusicStyleName,
+        first_index: u32,
+        new_subs: MusicSubStyles,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) {
+        for (index, sub) in (first_index..).zip(new_subs) {
+            <SubStyleIndex<T>>::insert(&sub, parent, ());
+            Self::note_diff(
+                TaxonomyDiff::AddSub {
+                    parent: parent.clone(),
+                    name: sub,
+                    index,
+                },
+                who.clone(),
+                reason.clone(),
+            );
+        }
+    }
+
+    /// Record a diff already applied to the styles tree in the history and the change log, emit
+    /// its event and notify the dependent pallets. Returns the new taxonomy version
+    pub(super) fn note_diff(
+        diff: TaxonomyDiff,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> u64 {
+        let version = Self::record_diff(diff.clone());
+
+        match diff {
+            TaxonomyDiff::AddParent { name, .. } => {
+                let style = StyleRef::Parent(name.clone());
+                Self::log_change(LogOperation::Added(style.clone()), who.clone(), reason);
+                Self::deposit_event(Event::StyleAdded { name, who, version });
+                T::OnStyleChange::on_added(&style);
+            }
+            TaxonomyDiff::AddSub { parent, name, .. } => {
+                let style = StyleRef::Sub(parent.clone(), name.clone());
+                Self::log_change(LogOperation::Added(style.clone()), who.clone(), reason);
+                Self::deposit_event(Event::SubStyleAdded {
+                    parent,
+                    name,
+                    who,
+                    version,
+                });
+                T::OnStyleChange::on_added(&style);
+            }
+            TaxonomyDiff::RenameParent { old_name, new_name } => {
+                Self::log_change(
+                    LogOperation::Renamed {
+                        style: StyleRef::Parent(old_name.clone()),
+                        new_name: new_name.clone(),
+                    },
+                    who.clone(),
+                    reason,
+                );
+                Self::deposit_event(Event::StyleNameUpdated {
+                    old_name: old_name.clone(),
+                    new_name: new_name.clone(),
+                    who,
+                    version,
+                });
+                T::OnStyleChange::on_renamed(
+                    &StyleRef::Parent(old_name),
+                    &StyleRef::Parent(new_name),
+                );
+            }
+            TaxonomyDiff::RenameSub {
+                parent,
+                old_name,
+                new_name,
+                ..
+            } => {
+                Self::log_change(
+                    LogOperation::Renamed {
+                        style: StyleRef::Sub(parent.clone(), old_name.clone()),
+                        new_name: new_name.clone(),
+                    },
+                    who.clone(),
+                    reason,
+                );
+                Self::deposit_event(Event::SubStyleNameUpdated {
+                    parent: parent.clone(),
+                    old_name: old_name.clone(),
+                    new_name: new_name.clone(),
+                    who,
+                    version,
+                });
+                T::OnStyleChange::on_renamed(
+                    &StyleRef::Sub(parent.clone(), old_name),
+                    &StyleRef::Sub(parent, new_name),
+                );
+            }
+            TaxonomyDiff::RemoveParent { name, subs } => {
+                Self::log_change(
+                    LogOperation::Removed(StyleRef::Parent(name.clone())),
+                    who.clone(),
+                    reason,
+                );
+                Self::deposit_event(Event::StyleRemoved {
+                    name: name.clone(),
+                    who,
+                    version,
+                });
+
+                // Sub styles are notified before their parent style
+                for sub in subs {
+                    T::OnStyleChange::on_removed(&StyleRef::Sub(name.clone(), sub));
+                }
+                T::OnStyleChange::on_removed(&StyleRef::Parent(name));
+            }
+            TaxonomyDiff::RemoveSub { parent, name, .. } => {
+                let style = StyleRef::Sub(parent.clone(), name.clone());
+                Self::log_change(LogOperation::Removed(style.clone()), who.clone(), reason);
+                Self::deposit_event(Event::SubStyleRemoved {
+                    parent,
+                    name,
+                    who,
+                    version,
+                });
+                T::OnStyleChange::on_removed(&style);
+            }
+            TaxonomyDiff::MoveSub {
+                name,
+                parent,
+                new_parent,
+                ..
+            } => {
+                Self::log_change(
+                    LogOperation::Moved {
+                        style: StyleRef::Sub(parent.clone(), name.clone()),
+                        new_parent: new_parent.clone(),
+                    },
+                    who.clone(),
+                    reason,
+                );
+                Self::deposit_event(Event::SubStyleMoved {
+                    parent: parent.clone(),
+                    new_parent: new_parent.clone(),
+                    name: name.clone(),
+                    who,
+                    version,
+                });
+                T::OnStyleChange::on_moved(
+                    &StyleRef::Sub(parent, name.clone()),
+                    &StyleRef::Sub(new_parent, name),
+                );
+            }
+            TaxonomyDiff::ReorderSubs { parent, .. } => {
+                Self::log_change(LogOperation::Reordered(parent.clone()), who.clone(), reason);
+                Self::deposit_event(Event::SubStylesReordered {
+                    parent,
+                    who,
+                    version,
+                });
+            }
+        }
+
+        version
+    }
+
+    /// Record a change of the styles in the change log, the entries older than
+    /// `MaxChangeLogEntries` are pruned
+    pub(super) fn log_change(
+        operation: LogOperation,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) {
+        let index = <NextLogIndex<T>>::get();
+        <NextLogIndex<T>>::put(index.saturating_add(1));
+
+        <ChangeLog<T>>::insert(
+            index,
+            ChangeLogEntry {
+                operation,
+                who,
+                block: frame_system::Pallet::<T>::block_number(),
+                reason,
+            },
+        );
+        if let Some(pruned) = index.checked_sub(T::MaxChangeLogEntries::get().into()) {
+            <ChangeLog<T>>::remove(pruned);
+        }
+    }
+
+    /// Bump the version of the styles tree and record the change in the history, the diffs older
+    /// than `MaxHistory` versions are pruned. The hash of the tree is cleared until the next
+    /// block. Returns the new version
+    pub(super) fn record_diff(diff: TaxonomyDiff) -> u64 {
+        let version = <TaxonomyVersion<T>>::mutate(|version| {
+            *version = version.saturating_add(1);
+            *version
+        });
+        <TaxonomyHash<T>>::kill();
+
+        <History<T>>::insert(version, diff);
+        if let Some(pruned) = version.checked_sub(T::MaxHistory::get().into()) {
+            <History<T>>::remove(pruned);
+            <IrreversibleVersions<T>>::remove(pruned);
+        }
+
+        version
+    }
+
+    /// The styles tree, draft styles included, as it was at the given taxonomy version. `None`
+    /// if the version is in the future or older than the kept history
+    pub fn styles_at_version(version: u64) -> Option<MusicStyleDB> {
+        let current = <TaxonomyVersion<T>>::get();
+        if version > current {
+            return None;
+        }
+
+        let mut styles = Self::get_styles();
+        for v in (version + 1..=current).rev() {
+            <History<T>>::get(v)?.inverse().apply(&mut styles)?;
+        }
+
+        Some(styles)
+    }
+
+    /// Revert the last `count` changes of the styles tree by applying their inverse diffs, each
+    /// one being recorded as a new taxonomy version. The inverse diffs are all checked against
+    /// the tree before the first one is applied, the removals which dropped the status, the
+    /// references or the aliases of a style can't be reverted. Like `do_set_taxonomy`, the added
+    /// styles are not rate limited
+    pub(super) fn do_revert_changes(
+        count: u32,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        Self::ensure_not_paused()?;
+        Self::ensure_not_frozen()?;
+        ensure!(count <= T::MaxHistory::get(), Error::<T>::NotRevertible);
+
+        let current = <TaxonomyVersion<T>>::get();
+        let first = current
+            .checked_sub(count.into())
+            .ok_or(Error::<T>::NotRevertible)?;
+
+        let mut styles = Self::get_styles();
+        let mut inverses = Vec::with_capacity(count as usize);
+        for version in (first + 1..=current).rev() {
+            ensure!(
+                !<IrreversibleVersions<T>>::contains_key(version),
+                Error::<T>::NotRevertible
+            );
+            let inverse = <History<T>>::get(version)
+                .ok_or(Error::<T>::NotRevertible)?
+                .inverse();
+            inverse
+                .apply(&mut styles)
+                .ok_or(Error::<T>::NotRevertible)?;
+            inverses.push(inverse);
+        }
+
+        for inverse in inverses {
+            Self::apply_diff(inverse, who.clone(), reason.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the whole styles tree. The changes listed by [`diff`] are checked like enacted
+    /// ones, then the new tree is written at once and each change is recorded as a new taxonomy
+    /// version with its usual event and notifications. Referenced styles can't be removed, the
+    /// new styles are not rate limited
+    pub(super) fn do_set_taxonomy(
+        new: MusicStyleDB,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        Self::ensure_not_paused()?;
+        Self::ensure_not_frozen()?;
+
+        let diffs = Self::taxonomy_diffs(Self::get_styles(), &new)?;
+
+        Self::put_styles(new);
+        for diff in diffs {
+            Self::note_applied(diff, who.clone(), reason.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Diffs turning the `old` styles tree into the `new` one, the changes listed by [`diff`]
+    /// followed by the reordering of the sub styles whose order differs. Locked styles can't be
+    /// changed and the referenced names missing from the new tree can't be removed
+    ///
+    /// The new tree fits within the bounds and [`diff`] removes and moves styles out before adding
+    /// any, so the diffs only run out of room when sub styles are swapped between full parent
+    /// styles
+    pub(super) fn taxonomy_diffs(
+        mut old: MusicStyleDB,
+        new: &MusicStyleDB,
+    ) -> Result<Vec<TaxonomyDiff>, DispatchError> {
+        for subs in new.values() {
+            for (i, sub) in subs.iter().enumerate() {
+                ensure!(!subs[..i].contains(sub), Error::<T>::NameAlreadyExists);
+            }
+        }
+
+        let names: BTreeSet<&MusicStyleName> = new
+            .iter()
+            .flat_map(|(parent, subs)| sp_std::iter::once(parent).chain(subs.iter()))
+            .collect();
+        let mut diffs = Vec::new();
+        for change in diff(&old, new) {
+            if !matches!(change, StyleChange::Add { .. }) {
+                Self::ensure_not_locked(&old, change.style())?;
+            }
+
+            let diff = change.to_diff(&old).ok_or(Error::<T>::StyleNotFound)?;
+            let removed: Vec<&MusicStyleName> = match &diff {
+                TaxonomyDiff::RemoveParent { name, subs } => {
+                    subs.iter().chain(sp_std::iter::once(name)).collect()
+                }
+                TaxonomyDiff::RemoveSub { name, .. } => vec![name],
+                _ => Vec::new(),
+            };
+            let in_use = removed.into_iter().any(|name| {
+                !names.contains(name) && !<StyleReferenceCount<T>>::get(name).is_zero()
+            });
+            ensure!(!in_use, Error::<T>::StyleInUse);
+
+            Self::apply_to(&mut old, &diff)?;
+            diffs.push(diff);
+        }
+
+        for (parent, new_subs) in new.iter() {
+            let Some(old_subs) = old.get(parent) else {
+                continue;
+            };
+            if old_subs != new_subs {
+                ensure!(
+                    !<LockedStyles<T>>::contains_key(StyleRef::Parent(parent.clone())),
+                    Error::<T>::StyleFrozen
+                );
+                diffs.push(TaxonomyDiff::ReorderSubs {
+                    parent: parent.clone(),
+                    old_subs: old_subs.clone(),
+                    new_subs: new_subs.clone(),
+                });
+            }
+        }
+
+        Ok(diffs)
+    }
+
+    /// Apply a diff to a styles tree, failing with `StylesCapacity` when the tree has no room left
+    /// for the style it adds and with `StyleNotFound` when it doesn't match the tree
+    fn apply_to(tree: &mut MusicStyleDB, diff: &TaxonomyDiff) -> DispatchResult {
+        if diff.apply(tree).is_some() {
+            return Ok(());
+        }
+
+        if Self::is_full(tree, diff) {
+            Err(Error::<T>::StylesCapacity.into())
+        } else {
+            Err(Error::<T>::StyleNotFound.into())
+        }
+    }
+
+    /// Whether the styles tree has no room left for the style added by a diff
+    fn is_full(tree: &MusicStyleDB, diff: &TaxonomyDiff) -> bool {
+        let is_full = |parent| {
+            tree.get(parent)
+                .is_some_and(|subs| subs.len() as u32 >= <MaxSubStyles as Get<u32>>::get())
+        };
+        match diff {
+            TaxonomyDiff::AddParent { .. } => {
+                tree.len() as u32 >= <MaxParentStyles as Get<u32>>::get()
+            }
+            TaxonomyDiff::AddSub { parent, .. } => is_full(parent),
+            TaxonomyDiff::MoveSub { new_parent, .. } => is_full(new_parent),
+            _ => false,
+        }
+    }
+
+    /// Record a diff once applied to the stored styles tree: the status, the references and the
+    /// aliases of a name no longer used are carried over its new name or dropped, the dropping
+    /// removals and the renames dropping a status can't be reverted
+    pub(super) fn note_applied(
+        diff: TaxonomyDiff,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) {
+        let mut lossy = false;
+        match &diff {
+            TaxonomyDiff::RenameParent { old_name, new_name }
+            | TaxonomyDiff::RenameSub {
+                old_name, new_name, ..
+            } => {
+                if !Self::name_exists(old_name) {
+                    lossy |= Self::move_name_state(old_name, new_name);
+                }
+            }
+            TaxonomyDiff::RemoveParent { name, subs } => {
+                for removed in subs.iter().chain(sp_std::iter::once(name)) {
+                    if !Self::name_exists(removed) {
+                        lossy |= Self::clear_name_state(removed);
                     }
                 }
+            }
+            TaxonomyDiff::RemoveSub { name, .. } => {
+                if !Self::name_exists(name) {
+                    lossy |= Self::clear_name_state(name);
+                }
+            }
+            _ => {}
+        }
 
-                Ok(())
+        let version = Self::note_diff(diff, who, reason);
+        if lossy {
+            <IrreversibleVersions<T>>::insert(version, ());
+        }
+    }
+
+    /// Store the chunks of an import, they are staged by `process_import`
+    pub(super) fn do_queue_import(
+        chunks: ImportChunks,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        Self::ensure_not_paused()?;
+        ensure!(!<Import<T>>::exists(), Error::<T>::ImportInProgress);
+        ensure!(!chunks.is_empty(), Error::<T>::EmptyImport);
+
+        let total = chunks.len() as u32;
+        let sizes = chunks.iter().map(Self::styles_count).collect::<Vec<_>>();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            <QueuedChunks<T>>::insert(i as u32, chunk);
+        }
+        <Import<T>>::put(PendingImport {
+            sizes: BoundedVec::truncate_from(sizes),
+            staged: 0,
+            swap_weight: None,
+            who: who.clone(),
+            reason,
+        });
+        Self::deposit_event(Event::ImportQueued { chunks: total, who });
+
+        Ok(())
+    }
+
+    /// Stage the chunks of the pending import within the given weight, then swap the styles tree
+    /// for the staged one once every chunk is staged. Nothing is done while the pallet is paused
+    /// or migrated. Returns the consumed weight
+    pub(super) fn process_import(limit: Weight) -> Weight {
+        let mut weight = T::DbWeight::get().reads(2);
+        if !limit.all_gte(weight) {
+            return Weight::zero();
+        }
+        let Some(mut import) = <Import<T>>::get() else {
+            return weight;
+        };
+        if Self::is_paused() || Self::is_migrating() {
+            return weight.saturating_add(T::DbWeight::get().reads(1));
+        }
+
+        let staged = import.staged;
+        while let Some(size) = import.sizes.get(import.staged as usize).copied() {
+            let step = T::Weights::stage_import(size);
+            if !limit.all_gte(weight.saturating_add(step)) {
+                break;
             }
-            // No existing sub style, we can insert without any check
-            None => {
-                tree.try_insert(into, subs.clone())
+            weight.saturating_accrue(step);
+
+            let chunk = <QueuedChunks<T>>::take(import.staged).unwrap_or_default();
+            import.staged += 1;
+            if let Err(error) = Self::stage_chunk(chunk) {
+                return weight.saturating_add(Self::fail_import(&import, error));
+            }
+            Self::deposit_event(Event::ImportProgress {
+                staged: import.staged,
+                total: import.total(),
+            });
+        }
+
+        if import.staged > staged {
+            <Import<T>>::put(&import);
+        }
+        if import.staged < import.total() {
+            return weight;
+        }
+
+        weight.saturating_add(Self::swap_import(import, limit.saturating_sub(weight)))
+    }
+
+    /// Swap the styles tree for the staged one in a single step, once a block has enough weight
+    /// left for it. Every diff between the trees is computed and checked before the parent styles
+    /// which differ are written at once, then the diffs are recorded with their usual events and
+    /// notifications, without rate limiting. A failed check drops the import and leaves the styles
+    /// tree untouched. Returns the consumed weight
+    fn swap_import(mut import: PendingImport<T::AccountId>, limit: Weight) -> Weight {
+        let cleanup = T::Weights::cancel_import(0, <StagedStyles<T>>::count());
+        let weight = T::Weights::swap_import(import.styles(), <ParentStyles<T>>::count())
+            .saturating_add(cleanup);
+        if !limit.all_gte(weight.max(import.swap_weight.unwrap_or_default())) {
+            return Weight::zero();
+        }
+
+        if let Err(error) = Self::ensure_not_frozen() {
+            return weight.saturating_add(Self::fail_import(&import, error));
+        }
+
+        let mut staged = MusicStyleDB::new();
+        for (parent, subs) in <StagedStyles<T>>::iter() {
+            // The number of staged parent styles is bounded when they are staged
+            let _ = staged.try_insert(parent, subs);
+        }
+        let current = Self::get_styles();
+        let diffs = match Self::taxonomy_diffs(current.clone(), &staged) {
+            Ok(diffs) => diffs,
+            Err(error) => return weight.saturating_add(Self::fail_import(&import, error)),
+        };
+
+        let notifications = diffs.iter().map(TaxonomyDiff::notifications).sum();
+        let total = T::Weights::apply_import_diff()
+            .saturating_mul(diffs.len() as u64)
+            .saturating_add(Self::notifications_weight(notifications))
+            .saturating_add(weight);
+        if !T::BlockWeights::get().max_block.all_gte(total) {
+            return weight.saturating_add(Self::fail_import(
+                &import,
+                Error::<T>::ImportTooLarge.into(),
+            ));
+        }
+        if !limit.all_gte(total) {
+            // The swap waits for a block with enough idle weight, the tree is left untouched
+            if import.swap_weight != Some(total) {
+                import.swap_weight = Some(total);
+                <Import<T>>::put(&import);
+            }
+            return weight;
+        }
+
+        Self::write_styles(&current, &staged);
+        for diff in diffs {
+            Self::note_applied(diff, import.who.clone(), import.reason.clone());
+        }
+        Self::clear_import();
+        Self::deposit_event(Event::ImportCompleted {
+            version: <TaxonomyVersion<T>>::get(),
+            who: import.who,
+        });
+
+        total
+    }
+
+    /// Apply a diff to the stored styles tree, only the parent styles it changes are read and
+    /// written. The number of parent styles isn't checked, they are added by `do_add_style`
+    pub(super) fn put_diff(diff: &TaxonomyDiff) -> DispatchResult {
+        let current = Self::styles_of(diff.parents());
+
+        let mut styles = current.clone();
+        Self::apply_to(&mut styles, diff)?;
+        Self::write_styles(&current, &styles);
+
+        Ok(())
+    }
+
+    /// Merge a chunk of the import into the staged styles tree
+    pub(super) fn stage_chunk(chunk: MusicStyleDB) -> DispatchResult {
+        for (parent, subs) in chunk {
+            let mut staged = match <StagedStyles<T>>::get(&parent) {
+                Some(staged) => staged,
+                None => {
+                    ensure!(
+                        <StagedStyles<T>>::count() < <MaxParentStyles as Get<u32>>::get(),
+                        Error::<T>::StylesCapacity
+                    );
+                    Default::default()
+                }
+            };
+            Self::checked_add_subs(&mut staged, &subs)?;
+            <StagedStyles<T>>::insert(parent, staged);
+        }
+
+        Ok(())
+    }
+
+    /// Drop an import whose chunk can't be staged, returns the weight of the cleanup
+    fn fail_import(import: &PendingImport<T::AccountId>, error: DispatchError) -> Weight {
+        let weight = T::Weights::cancel_import(
+            import.total().saturating_sub(import.staged),
+            <StagedStyles<T>>::count(),
+        );
+        Self::clear_import();
+        Self::deposit_event(Event::ImportFailed { error });
+
+        weight
+    }
+
+    /// Remove the pending import along with its queued and staged chunks
+    pub(super) fn clear_import() {
+        <Import<T>>::kill();
+        let _ = <QueuedChunks<T>>::clear(u32::MAX, None);
+        let _ = <StagedStyles<T>>::clear(u32::MAX, None);
+    }
+
+    /// Number of parent styles and sub styles of a styles tree
+    pub(super) fn styles_count(styles: &MusicStyleDB) -> u32 {
+        styles.values().map(|subs| subs.len() as u32 + 1).sum()
+    }
+
+    /// Apply a diff to the styles tree with the same checks, records, events and notifications
+    /// as the change it stands for. The added styles are not rate limited
+    fn apply_diff(
+        diff: TaxonomyDiff,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        let change = match diff {
+            TaxonomyDiff::AddParent { name, subs } => {
+                return Self::add_style(name, subs, false, who, reason)
+            }
+            TaxonomyDiff::AddSub {
+                parent,
+                name,
+                index,
+            } => {
+                let mut subs = <ParentStyles<T>>::get(&parent).ok_or(Error::<T>::StyleNotFound)?;
+                ensure!(!subs.contains(&name), Error::<T>::NameAlreadyExists);
+                subs.try_insert(index as usize, name.clone())
                     .map_err(|_| Error::<T>::StylesCapacity)?;
 
-                Ok(())
+                <ParentStyles<T>>::insert(&parent, subs);
+                Self::note_added_subs(
+                    &parent,
+                    index,
+                    BoundedVec::truncate_from(vec![name]),
+                    who,
+                    reason,
+                );
+                return Ok(());
+            }
+            TaxonomyDiff::RenameParent { old_name, new_name } => StyleChange::Rename {
+                style: StyleRef::Parent(old_name),
+                new_name,
+            },
+            TaxonomyDiff::RenameSub {
+                parent,
+                old_name,
+                new_name,
+                ..
+            } => StyleChange::Rename {
+                style: StyleRef::Sub(parent, old_name),
+                new_name,
+            },
+            TaxonomyDiff::RemoveParent { name, .. } => StyleChange::Remove {
+                style: StyleRef::Parent(name),
+                force: false,
+            },
+            TaxonomyDiff::RemoveSub { parent, name, .. } => StyleChange::Remove {
+                style: StyleRef::Sub(parent, name),
+                force: false,
+            },
+            TaxonomyDiff::MoveSub {
+                ref name,
+                ref parent,
+                ref new_parent,
+                ..
+            } => {
+                let change = StyleChange::Move {
+                    style: StyleRef::Sub(parent.clone(), name.clone()),
+                    new_parent: new_parent.clone(),
+                };
+                Self::check_change(&Self::styles_of(change.parents()), &change)?;
+                Self::put_diff(&diff)?;
+                Self::note_applied(diff, who, reason);
+                return Ok(());
+            }
+            TaxonomyDiff::ReorderSubs {
+                ref parent,
+                ref old_subs,
+                ref new_subs,
+            } => {
+                ensure!(
+                    <ParentStyles<T>>::get(parent).as_ref() == Some(old_subs),
+                    Error::<T>::NotRevertible
+                );
+
+                <ParentStyles<T>>::insert(parent, new_subs);
+                Self::note_diff(diff, who, reason);
+                return Ok(());
+            }
+        };
+
+        Self::enact_change(change, who, reason).map(|_| ())
+    }
+
+    /// Blake2-256 hash of the SCALE encoded styles tree, including the draft styles. It is
+    /// computed on the fly for trees which changed since the beginning of the block
+    pub fn taxonomy_hash() -> H256 {
+        <TaxonomyHash<T>>::get().unwrap_or_else(Self::hash_styles)
+    }
+
+    pub(super) fn hash_styles() -> H256 {
+        BlakeTwo256::hash_of(&Self::get_styles())
+    }
+
+    /// Store the hash of the styles tree if it changed since it was last hashed, the tree is not
+    /// hashed while it is migrated. Returns the consumed weight
+    pub(super) fn refresh_taxonomy_hash() -> Weight {
+        let weight = T::DbWeight::get().reads(2);
+        if <TaxonomyHash<T>>::exists() || Self::is_migrating() {
+            return weight;
+        }
+
+        <TaxonomyHash<T>>::put(Self::hash_styles());
+        weight.saturating_add(T::Weights::refresh_taxonomy_hash(<ParentStyles<T>>::count()))
+    }
+
+    /// Account for newly created styles in the current rate limiting period
+    pub(super) fn note_new_styles(parents: u32, subs: u32) -> DispatchResult {
+        let new_parents = <NewParentStyles<T>>::get().saturating_add(parents);
+        let new_subs = <NewSubStyles<T>>::get().saturating_add(subs);
+
+        ensure!(
+            new_parents <= T::MaxNewParentStylesPerPeriod::get()
+                && new_subs <= T::MaxNewSubStylesPerPeriod::get(),
+            Error::<T>::RateLimited
+        );
+
+        <NewParentStyles<T>>::put(new_parents);
+        <NewSubStyles<T>>::put(new_subs);
+
+        Ok(())
+    }
+
+    pub(super) fn to_style_ref(
+        parent: Vec<u8>,
+        sub: Option<Vec<u8>>,
+    ) -> Result<StyleRef, DispatchError> {
+        let parent = Self::to_bounded_style(parent)?;
+
+        Ok(match sub {
+            Some(sub) => StyleRef::Sub(parent, Self::to_bounded_style(sub)?),
+            None => StyleRef::Parent(parent),
+        })
+    }
+
+    pub(super) fn ensure_style_exists(tree: &MusicStyleDB, style: &StyleRef) -> DispatchResult {
+        let subs = tree.get(style.parent()).ok_or(Error::<T>::StyleNotFound)?;
+        if let StyleRef::Sub(_, name) = style {
+            ensure!(subs.contains(name), Error::<T>::StyleNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Reject the mutating calls while the pallet is paused or its styles tree is migrated
+    pub(super) fn ensure_not_paused() -> DispatchResult {
+        ensure!(!Self::is_paused(), Error::<T>::PalletPaused);
+        ensure!(!Self::is_migrating(), Error::<T>::MigrationInProgress);
+        Ok(())
+    }
+
+    /// Whether the styles tree is being migrated by `migrations::v1::LazyMigrateToV1`
+    pub fn is_migrating() -> bool {
+        <StylesMigration<T>>::exists()
+    }
+
+    pub(super) fn ensure_not_frozen() -> DispatchResult {
+        ensure!(!Self::is_frozen(), Error::<T>::StyleFrozen);
+        Ok(())
+    }
+
+    /// Ensure that neither the given style nor, for a parent style, one of its sub styles is
+    /// locked
+    pub(super) fn ensure_not_locked(tree: &MusicStyleDB, style: &StyleRef) -> DispatchResult {
+        ensure!(
+            !<LockedStyles<T>>::contains_key(style),
+            Error::<T>::StyleFrozen
+        );
+
+        if let StyleRef::Parent(parent) = style {
+            let locked_sub = tree.get(parent).is_some_and(|subs| {
+                subs.iter().any(|sub| {
+                    <LockedStyles<T>>::contains_key(StyleRef::Sub(parent.clone(), sub.clone()))
+                })
+            });
+            ensure!(!locked_sub, Error::<T>::StyleFrozen);
+        }
+
+        Ok(())
+    }
+
+    /// Check that the given change could be applied to the stored styles tree. `tree` only needs
+    /// to hold the parent styles involved in the change, see [`Self::styles_of`]
+    pub(super) fn check_change(tree: &MusicStyleDB, change: &StyleChange) -> DispatchResult {
+        let style = change.style();
+
+        Self::ensure_not_paused()?;
+        Self::ensure_not_frozen()?;
+
+        if let StyleChange::Add { style } = change {
+            let name_taken = match style {
+                StyleRef::Parent(name) => tree.contains_key(name),
+                StyleRef::Sub(parent, name) => tree
+                    .get(parent)
+                    .ok_or(Error::<T>::StyleNotFound)?
+                    .contains(name),
+            };
+            ensure!(!name_taken, Error::<T>::NameAlreadyExists);
+            return Ok(());
+        }
+
+        Self::ensure_style_exists(tree, style)?;
+        Self::ensure_not_locked(tree, style)?;
+
+        let subs = tree.get(style.parent()).ok_or(Error::<T>::StyleNotFound)?;
+
+        if let StyleChange::Rename { new_name, .. } = change {
+            let name_taken = match style {
+                StyleRef::Parent(_) => tree.contains_key(new_name),
+                StyleRef::Sub(..) => subs.contains(new_name),
+            };
+            ensure!(!name_taken, Error::<T>::NameAlreadyExists);
+        }
+
+        if let StyleChange::Remove { force: false, .. } = change {
+            let in_use = Self::removed_names(tree, style)
+                .iter()
+                .any(|name| !<StyleReferenceCount<T>>::get(name).is_zero());
+            ensure!(!in_use, Error::<T>::StyleInUse);
+        }
+
+        if let StyleChange::Move { new_parent, .. } = change {
+            let StyleRef::Sub(parent, name) = style else {
+                return Err(Error::<T>::InvalidMove.into());
+            };
+            let new_subs = tree.get(new_parent).ok_or(Error::<T>::InvalidMove)?;
+            ensure!(parent != new_parent, Error::<T>::InvalidMove);
+            ensure!(!new_subs.contains(name), Error::<T>::NameAlreadyExists);
+        }
+
+        Ok(())
+    }
+
+    /// Validate a destructive change and schedule it `EnactmentDelay` blocks ahead
+    pub(super) fn schedule_change(
+        change: StyleChange,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> DispatchResult {
+        Self::check_change(&Self::styles_of(change.parents()), &change)?;
+
+        let when =
+            frame_system::Pallet::<T>::block_number().saturating_add(T::EnactmentDelay::get());
+        let id = <NextChangeId<T>>::get();
+
+        <Agenda<T>>::try_mutate(when, |agenda| agenda.try_push(id))
+            .map_err(|_| Error::<T>::TooManyScheduledChanges)?;
+        <NextChangeId<T>>::put(id.wrapping_add(1));
+        <ScheduledChanges<T>>::insert(
+            id,
+            ScheduledChange {
+                change: change.clone(),
+                when,
+                who: who.clone(),
+                reason,
+            },
+        );
+
+        Self::deposit_event(Event::StyleChangeScheduled {
+            id,
+            change,
+            when,
+            who,
+        });
+
+        Ok(())
+    }
+
+    /// Enact the changes scheduled at the given block, each one in its own storage layer so a
+    /// failing change is dropped without affecting the others. The deferred changes go first, as
+    /// many of them as the agenda leaves room for. Returns the consumed weight
+    pub(super) fn enact_agenda(now: BlockNumberFor<T>) -> Weight {
+        let agenda = <Agenda<T>>::take(now);
+        let room = T::MaxScheduledPerBlock::get().saturating_sub(agenda.len() as u32);
+        let deferred = Self::take_deferred(room);
+        let taken = deferred.len() as u64;
+        let count = (agenda.len() + deferred.len()) as u32;
+        let mut notifications = 0;
+
+        for id in deferred.into_iter().chain(agenda) {
+            if let Some(scheduled) = <ScheduledChanges<T>>::take(id) {
+                let result = frame_support::storage::with_storage_layer(|| {
+                    Self::enact_change(scheduled.change, scheduled.who, scheduled.reason)
+                });
+
+                match result {
+                    Ok(notified) => {
+                        notifications += notified;
+                        Self::deposit_event(Event::StyleChangeEnacted { id })
+                    }
+                    Err(error) => Self::deposit_event(Event::StyleChangeFailed { id, error }),
+                }
+            }
+        }
+
+        T::Weights::enact_changes(count)
+            .saturating_add(T::DbWeight::get().reads_writes(taken + 1, taken + 1))
+            .saturating_add(Self::notifications_weight(notifications))
+    }
+
+    /// Append the changes scheduled at the given block to the deferred changes, they are enacted
+    /// once the pallet is unpaused. Returns the number of deferred changes
+    pub(super) fn defer_agenda(now: BlockNumberFor<T>) -> u32 {
+        let agenda = <Agenda<T>>::take(now);
+        let count = agenda.len() as u32;
+
+        let (first, mut end) = <DeferredRange<T>>::get();
+        for id in agenda {
+            <DeferredChanges<T>>::insert(end, id);
+            end = end.wrapping_add(1);
+            Self::deposit_event(Event::StyleChangeDeferred { id });
+        }
+        <DeferredRange<T>>::put((first, end));
+
+        count
+    }
+
+    /// Take up to `max` deferred changes, the oldest ones first
+    fn take_deferred(max: u32) -> Vec<ChangeId> {
+        let (mut first, end) = <DeferredRange<T>>::get();
+        let mut ids = Vec::new();
+        while first != end && (ids.len() as u32) < max {
+            ids.extend(<DeferredChanges<T>>::take(first));
+            first = first.wrapping_add(1);
+        }
+
+        if first == end {
+            <DeferredRange<T>>::kill();
+        } else {
+            <DeferredRange<T>>::put((first, end));
+        }
+
+        ids
+    }
+
+    /// Weight of notifying the given number of changes to the dependent pallets
+    pub(super) fn notifications_weight(count: u32) -> Weight {
+        T::OnStyleChange::notification_weight().saturating_mul(count.into())
+    }
+
+    /// Apply a change to the styles tree, emit the related events and notify the dependent
+    /// pallets. The change is checked again since the tree could have changed since its
+    /// scheduling. Only the parent styles involved in the change are read and written. Returns
+    /// the number of notifications sent.
+    pub(super) fn enact_change(
+        change: StyleChange,
+        who: Option<T::AccountId>,
+        reason: Option<ChangeReason>,
+    ) -> Result<u32, DispatchError> {
+        let styles = Self::styles_of(change.parents());
+
+        Self::check_change(&styles, &change)?;
+
+        match change {
+            StyleChange::Add {
+                style: StyleRef::Parent(name),
+            } => Self::do_add_style(name, Default::default(), who, reason).map(|_| 1),
+            StyleChange::Add {
+                style: StyleRef::Sub(parent, name),
+            } => {
+                Self::do_add_sub_styles(parent, BoundedVec::truncate_from(vec![name]), who, reason)
+                    .map(|_| 1)
             }
+            change => {
+                let diff = change.to_diff(&styles).ok_or(Error::<T>::StyleNotFound)?;
+                let notified = diff.notifications();
+
+                Self::put_diff(&diff)?;
+                Self::note_applied(diff, who, reason);
+
+                Ok(notified)
+            }
+        }
+    }
+
+    /// Whether the name is used by a parent style or a sub style of the tree
+    pub(super) fn contains_name(tree: &MusicStyleDB, name: &MusicStyleName) -> bool {
+        tree.contains_key(name) || tree.values().any(|subs| subs.contains(name))
+    }
+
+    /// Status of a style name, styles without explicit status are active
+    pub fn status_of(name: &MusicStyleName) -> StyleStatus {
+        <StyleStatuses<T>>::get(name).unwrap_or_default()
+    }
+
+    /// Names which would not be used anymore in the stored styles tree once the style is removed.
+    /// `tree` only needs to hold the parent style of the style, the other uses of the names are
+    /// looked up in the storage
+    pub(super) fn removed_names(tree: &MusicStyleDB, style: &StyleRef) -> Vec<MusicStyleName> {
+        let parent = style.parent();
+        let Some(subs) = tree.get(parent) else {
+            return Vec::new();
+        };
+        let removed: Vec<&MusicStyleName> = match style {
+            StyleRef::Parent(name) => subs.iter().chain(sp_std::iter::once(name)).collect(),
+            StyleRef::Sub(_, name) => vec![name],
+        };
+        let removed_parent = matches!(style, StyleRef::Parent(_)).then_some(parent);
+
+        removed
+            .into_iter()
+            .filter(|name| {
+                let parent_kept =
+                    removed_parent != Some(*name) && <ParentStyles<T>>::contains_key(name);
+                let sub_kept = <SubStyleIndex<T>>::iter_key_prefix(name).any(|p| p != *parent);
+                !parent_kept && !sub_kept
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Drop the status, the references count and the aliases of a name. Returns whether any of
+    /// them was dropped
+    fn clear_name_state(name: &MusicStyleName) -> bool {
+        let mut cleared = <StyleStatuses<T>>::take(name).is_some();
+        cleared |= !<StyleReferenceCount<T>>::take(name).is_zero();
+        for (alias, _) in <AliasIndex<T>>::drain_prefix(name) {
+            <StyleAliases<T>>::remove(alias);
+            cleared = true;
         }
+
+        cleared
+    }
+
+    /// Make an alias resolve to the given style name
+    pub(super) fn put_alias(alias: MusicStyleName, name: MusicStyleName) {
+        <AliasIndex<T>>::insert(&name, &alias, ());
+        <StyleAliases<T>>::insert(alias, name);
+    }
+
+    /// Move the status, the references and the aliases of a name over another one. A new name
+    /// already used elsewhere in the tree keeps its own status, the status of the old name is
+    /// then dropped. Returns whether it was dropped
+    fn move_name_state(old_name: &MusicStyleName, new_name: &MusicStyleName) -> bool {
+        let mut dropped = false;
+        if let Some(status) = <StyleStatuses<T>>::take(old_name) {
+            dropped = <StyleStatuses<T>>::contains_key(new_name);
+            if !dropped {
+                <StyleStatuses<T>>::insert(new_name, status);
+            }
+        }
+
+        let references = <StyleReferenceCount<T>>::take(old_name);
+        if !references.is_zero() {
+            <StyleReferenceCount<T>>::mutate(new_name, |count| {
+                *count = count.saturating_add(references)
+            });
+        }
+
+        for (alias, _) in <AliasIndex<T>>::drain_prefix(old_name) {
+            Self::put_alias(alias, new_name.clone());
+        }
+
+        dropped
+    }
+
+    /// The styles tree without the draft styles
+    pub(super) fn visible_styles() -> MusicStyleDB {
+        let mut styles: MusicStyleDB = Self::get_styles();
+
+        let parents: Vec<MusicStyleName> = styles.keys().cloned().collect();
+        for parent in parents {
+            if !Self::status_of(&parent).is_visible() {
+                styles.remove(&parent);
+            } else if let Some(subs) = styles.get_mut(&parent) {
+                subs.retain(|sub| Self::status_of(sub).is_visible());
+            }
+        }
+
+        styles
     }
-}
+}
\ No newline at end of file
@@ -0,0 +1,306 @@
+//! Genesis styles and their chain spec format
+//!
+//! The genesis styles are written as an object whose keys are the parent styles, each one holding
+//! either the list of its sub styles or an object with its sub styles and metadata:
+//!
+//! ```json
+//! {
+//!   "Rap": ["Drill", "Trap"],
+//!   "Rock": { "subs": ["Hardcore"], "status": "active", "aliases": ["Rock'n'Roll"] },
+//!   "Raggae": { "status": { "deprecated": "Rock" } }
+//! }
+//! ```
+//!
+//! A list of parent styles is also accepted, each one given either as a `[name, [sub, ...]]` pair
+//! like the chain specs written before, or as an object with a `name` and the fields above. Names
+//! are strings, or bytes when they are not valid UTF-8, in which case the styles are written as a
+//! list.
+
+use super::*;
+use serde::{de, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A parent style of the genesis along with its sub styles and metadata
+#[derive(Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct GenesisStyle {
+    /// Name of the parent style
+    pub name: Vec<u8>,
+    /// Names of its sub styles
+    pub subs: Vec<Vec<u8>>,
+    /// Lifecycle status of the parent style, active when not given
+    pub status: Option<StyleStatus>,
+    /// Other names resolving to the parent style
+    pub aliases: Vec<Vec<u8>>,
+}
+
+impl GenesisStyle {
+    /// A parent style and its sub styles, without metadata
+    pub fn new<N: Into<Vec<u8>>>(name: N, subs: impl IntoIterator<Item = N>) -> Self {
+        Self {
+            name: name.into(),
+            subs: subs.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Validated statuses and aliases of the genesis styles
+#[derive(Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct GenesisMetadata {
+    /// Status of the parent styles which have one
+    pub statuses: Vec<(MusicStyleName, StyleStatus)>,
+    /// Aliases of the parent styles (alias, style)
+    pub aliases: Vec<(MusicStyleName, MusicStyleName)>,
+}
+
+/// A name written as a string, or as bytes when it isn't valid UTF-8
+struct Name(Vec<u8>);
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match sp_std::str::from_utf8(&self.0) {
+            Ok(name) => serializer.serialize_str(name),
+            Err(_) => serializer.collect_seq(self.0.iter()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NameVisitor;
+
+        impl<'de> de::Visitor<'de> for NameVisitor {
+            type Value = Name;
+
+            fn expecting(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+                f.write_str("a style name, as a string or as bytes")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Name, E> {
+                Ok(Name(value.as_bytes().to_vec()))
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Name, E> {
+                Ok(Name(value.to_vec()))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Name, A::Error> {
+                let mut name = Vec::new();
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    name.push(byte);
+                }
+                Ok(Name(name))
+            }
+        }
+
+        deserializer.deserialize_any(NameVisitor)
+    }
+}
+
+fn to_names(names: &[Vec<u8>]) -> Vec<Name> {
+    names.iter().cloned().map(Name).collect()
+}
+
+fn from_names(names: Vec<Name>) -> Vec<Vec<u8>> {
+    names.into_iter().map(|name| name.0).collect()
+}
+
+/// Lifecycle status as written in the chain spec, e.g. `"retired"` or `{"deprecated": "Trap"}`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+enum Status {
+    Draft,
+    Active,
+    Deprecated(Option<Name>),
+    Retired,
+}
+
+impl From<&StyleStatus> for Status {
+    fn from(status: &StyleStatus) -> Self {
+        match status {
+            StyleStatus::Draft => Status::Draft,
+            StyleStatus::Active => Status::Active,
+            StyleStatus::Deprecated(replacement) => {
+                Status::Deprecated(replacement.as_ref().map(|name| Name(name.to_vec())))
+            }
+            StyleStatus::Retired => Status::Retired,
+        }
+    }
+}
+
+impl Status {
+    fn into_style_status<E: de::Error>(self) -> Result<StyleStatus, E> {
+        Ok(match self {
+            Status::Draft => StyleStatus::Draft,
+            Status::Active => StyleStatus::Active,
+            Status::Deprecated(None) => StyleStatus::Deprecated(None),
+            Status::Deprecated(Some(Name(name))) => StyleStatus::Deprecated(Some(
+                MusicStyleName::try_from(name.clone())
+                    .map_err(|_| E::custom(GenesisError::NameTooLong(name)))?,
+            )),
+            Status::Retired => StyleStatus::Retired,
+        })
+    }
+}
+
+/// Sub styles and metadata of a parent style
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Details {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subs: Vec<Name>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<Name>,
+}
+
+impl From<&GenesisStyle> for Details {
+    fn from(style: &GenesisStyle) -> Self {
+        Details {
+            subs: to_names(&style.subs),
+            status: style.status.as_ref().map(Into::into),
+            aliases: to_names(&style.aliases),
+        }
+    }
+}
+
+impl Details {
+    fn into_style<E: de::Error>(self, name: Vec<u8>) -> Result<GenesisStyle, E> {
+        Ok(GenesisStyle {
+            name,
+            subs: from_names(self.subs),
+            status: self.status.map(Status::into_style_status).transpose()?,
+            aliases: from_names(self.aliases),
+        })
+    }
+}
+
+/// Value of a parent style in the object form
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Subs(Vec<Name>),
+    Details(Details),
+}
+
+/// A parent style in the list form
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NamedDetails {
+    name: Name,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subs: Vec<Name>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<Name>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ListEntry {
+    Pair(Name, Vec<Name>),
+    Details(NamedDetails),
+}
+
+/// Write the genesis styles as an object, or as a list if a parent style isn't valid UTF-8
+pub fn serialize<S: Serializer>(styles: &[GenesisStyle], serializer: S) -> Result<S::Ok, S::Error> {
+    if styles
+        .iter()
+        .all(|style| sp_std::str::from_utf8(&style.name).is_ok())
+    {
+        let mut map = serializer.serialize_map(Some(styles.len()))?;
+        for style in styles {
+            let entry = match (&style.status, style.aliases.is_empty()) {
+                (None, true) => Entry::Subs(to_names(&style.subs)),
+                _ => Entry::Details(style.into()),
+            };
+            map.serialize_entry(&Name(style.name.clone()), &entry)?;
+        }
+        map.end()
+    } else {
+        serializer.collect_seq(styles.iter().map(|style| NamedDetails {
+            name: Name(style.name.clone()),
+            subs: to_names(&style.subs),
+            status: style.status.as_ref().map(Into::into),
+            aliases: to_names(&style.aliases),
+        }))
+    }
+}
+
+/// Read the genesis styles from their object or list form
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<GenesisStyle>, D::Error> {
+    let mut styles = Vec::new();
+    deserialize_with(deserializer, |style| {
+        styles.push(style);
+        Ok::<_, GenesisError>(())
+    })?;
+    Ok(styles)
+}
+
+/// Read the parent styles from their object or list form, handing each one to `push` as soon as
+/// it is read so that its errors are reported at the position of the offending entry
+pub(crate) fn deserialize_with<'de, D, F, E>(deserializer: D, push: F) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnMut(GenesisStyle) -> Result<(), E>,
+    E: sp_std::fmt::Display,
+{
+    struct StylesVisitor<F>(F);
+
+    impl<'de, F, E> de::Visitor<'de> for StylesVisitor<F>
+    where
+        F: FnMut(GenesisStyle) -> Result<(), E>,
+        E: sp_std::fmt::Display,
+    {
+        type Value = ();
+
+        fn expecting(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+            f.write_str("an object or a list of parent styles")
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+            while let Some((Name(name), entry)) = map.next_entry::<Name, Entry>()? {
+                let style = match entry {
+                    Entry::Subs(subs) => GenesisStyle {
+                        name,
+                        subs: from_names(subs),
+                        ..Default::default()
+                    },
+                    Entry::Details(details) => details.into_style(name)?,
+                };
+                (self.0)(style).map_err(de::Error::custom)?;
+            }
+            Ok(())
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+            while let Some(entry) = seq.next_element::<ListEntry>()? {
+                let style = match entry {
+                    ListEntry::Pair(Name(name), subs) => GenesisStyle {
+                        name,
+                        subs: from_names(subs),
+                        ..Default::default()
+                    },
+                    ListEntry::Details(NamedDetails {
+                        name,
+                        subs,
+                        status,
+                        aliases,
+                    }) => Details {
+                        subs,
+                        status,
+                        aliases,
+                    }
+                    .into_style(name.0)?,
+                };
+                (self.0)(style).map_err(de::Error::custom)?;
+            }
+            Ok(())
+        }
+    }
+
+    deserializer.deserialize_any(StylesVisitor(push))
+}
\ No newline at end of file
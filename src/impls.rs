@@ -1,71 +1,163 @@
 use super::*;
+use crate::traits::{
+    InspectMusicStylesWeight, InspectStyleStatus, InspectTaxonomyVersion, StyleReferences,
+};
 use allfeat_support::traits::music::style::MutateMusicStyles;
+use frame_support::traits::{Contains, IsSubType};
 
+/// Draft styles are hidden from the inspection, only admin queries can see them
 impl<T: Config> InspectMusicStyles for Pallet<T> {
     type Styles = MusicStyleDB;
     type StyleName = MusicStyleName;
 
     fn styles() -> Self::Styles {
-        Pallet::<T>::get_styles()
+        Pallet::<T>::visible_styles()
     }
 
     fn parent_styles() -> Vec<Self::StyleName> {
-        Self::styles().keys().cloned().collect()
+        let mut parents: Vec<Self::StyleName> = <ParentStyles<T>>::iter_keys()
+            .filter(|parent| Self::status_of(parent).is_visible())
+            .collect();
+        parents.sort();
+        parents
     }
     fn sub_styles() -> Vec<Self::StyleName> {
-        let mut buf: Vec<Self::StyleName> = Vec::new();
-        Self::styles()
-            .values()
-            .for_each(|v| v.iter().cloned().for_each(|s| buf.push(s)));
-        buf
+        let mut styles: Vec<(Self::StyleName, MusicSubStyles)> = <ParentStyles<T>>::iter()
+            .filter(|(parent, _)| Self::status_of(parent).is_visible())
+            .collect();
+        styles.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        styles
+            .into_iter()
+            .flat_map(|(_, subs)| subs.into_iter())
+            .filter(|sub| Self::status_of(sub).is_visible())
+            .collect()
     }
     fn is_parent_style(style_name: &Self::StyleName) -> bool {
-        Self::parent_styles().contains(style_name)
+        Self::has_parent_style(style_name)
     }
     fn is_sub_style(style_name: &Self::StyleName) -> bool {
-        Self::sub_styles().contains(style_name)
+        Self::has_sub_style(style_name)
     }
     fn exist(style_name: &Self::StyleName) -> bool {
-        let styles = Self::styles();
+        Self::has_parent_style(style_name) || Self::has_sub_style(style_name)
+    }
+}
+
+/// Weights depend on the current number of parent styles, which also bounds the number of parent
+/// styles holding a given sub style
+impl<T: Config> InspectMusicStylesWeight for Pallet<T> {
+    fn parent_styles_weight() -> Weight {
+        T::Weights::parent_styles(<ParentStyles<T>>::count())
+            .saturating_add(T::DbWeight::get().reads(1))
+    }
+    fn sub_styles_weight() -> Weight {
+        T::Weights::sub_styles(<ParentStyles<T>>::count())
+            .saturating_add(T::DbWeight::get().reads(1))
+    }
+    fn is_parent_style_weight() -> Weight {
+        T::Weights::is_parent_style()
+    }
+    fn is_sub_style_weight() -> Weight {
+        T::Weights::is_sub_style(<ParentStyles<T>>::count())
+            .saturating_add(T::DbWeight::get().reads(1))
+    }
+    fn exist_weight() -> Weight {
+        T::Weights::exist(<ParentStyles<T>>::count()).saturating_add(T::DbWeight::get().reads(1))
+    }
+}
 
-        if styles.contains_key(style_name) {
-            return true;
+impl<T: Config> InspectStyleStatus for Pallet<T> {
+    // A sub style is only visible under a visible parent style
+    fn exist_with_status(style_name: &MusicStyleName) -> Option<StyleStatus> {
+        if !Self::has_parent_style(style_name) && !Self::has_sub_style(style_name) {
+            return None;
         }
 
-        styles.values().find(|v| v.contains(style_name)).is_some()
+        Some(Self::status_of(style_name))
+    }
+
+    fn replacement(style_name: &MusicStyleName) -> Option<MusicStyleName> {
+        match Self::exist_with_status(style_name) {
+            Some(StyleStatus::Deprecated(replacement)) => replacement,
+            _ => None,
+        }
+    }
+}
+
+impl<T: Config> InspectTaxonomyVersion for Pallet<T> {
+    fn taxonomy_version() -> u64 {
+        <TaxonomyVersion<T>>::get()
+    }
+
+    fn taxonomy_hash() -> H256 {
+        Pallet::<T>::taxonomy_hash()
     }
 }
 
-#[cfg(feature = "runtime-benchmarks")]
-/// This should only be used to create new style in a benchmarking behavior.
+impl<T: Config> StyleReferences for Pallet<T> {
+    // The references are frozen while an import is pending, the staged tree is checked against
+    // them only when it replaces the styles tree
+    fn inc_ref(style_name: &MusicStyleName) -> DispatchResult {
+        ensure!(!<Import<T>>::exists(), Error::<T>::ImportInProgress);
+        ensure!(Self::name_exists(style_name), Error::<T>::StyleNotFound);
+        // Draft styles and sub styles only held by draft parent styles are not visible
+        ensure!(
+            Self::exist_with_status(style_name)
+                .is_some_and(|status| status != StyleStatus::Retired),
+            Error::<T>::StyleNotUsable
+        );
+
+        <StyleReferenceCount<T>>::mutate(style_name, |count| *count = count.saturating_add(1));
+
+        Ok(())
+    }
+
+    fn dec_ref(style_name: &MusicStyleName) -> DispatchResult {
+        ensure!(!<Import<T>>::exists(), Error::<T>::ImportInProgress);
+        <StyleReferenceCount<T>>::try_mutate_exists(style_name, |count| {
+            let current = count.ok_or(Error::<T>::NotReferenced)?;
+            *count = Some(current - 1).filter(|c| !c.is_zero());
+            Ok(())
+        })
+    }
+
+    fn references(style_name: &MusicStyleName) -> u32 {
+        <StyleReferenceCount<T>>::get(style_name)
+    }
+}
+
+/// Call filter rejecting the mutating calls of the pallet while it is paused or its styles tree is
+/// migrated, it can be used as part of the runtime `BaseCallFilter`
+pub struct PauseFilter<T>(PhantomData<T>);
+
+impl<T: Config, C: IsSubType<Call<T>>> Contains<C> for PauseFilter<T> {
+    fn contains(call: &C) -> bool {
+        match call.is_sub_type() {
+            Some(Call::pause { .. })
+            | Some(Call::unpause { .. })
+            | Some(Call::resume_styles_migration { .. }) => true,
+            Some(_) => !Pallet::<T>::is_paused() && !Pallet::<T>::is_migrating(),
+            None => true,
+        }
+    }
+}
+
+/// Lets other pallets, like a governance or an import pallet, add styles with the same checks,
+/// events and notifications as the extrinsics
 impl<T: Config> MutateMusicStyles for Pallet<T> {
     type StyleName = MusicStyleName;
 
     fn add_parent_style(style_name: Self::StyleName) -> sp_runtime::DispatchResult {
-        pallet::Styles::<T>::try_mutate(|db| -> DispatchResult {
-            ensure!(!db.contains_key(&style_name), Error::<T>::NameAlreadyExists);
-            db.try_insert(style_name, Default::default())
-                .map_err(|_| Error::<T>::StylesCapacity)?;
-            Ok(())
-        })
+        Self::do_add_style(style_name, Default::default(), None, None)
     }
     fn add_sub_style(
         sub_style_name: Self::StyleName,
         parent_style: Self::StyleName,
     ) -> sp_runtime::DispatchResult {
-        pallet::Styles::<T>::try_mutate(|db| -> DispatchResult {
-            if let Some(sub_styles) = db.get_mut(&parent_style) {
-                ensure!(
-                    sub_styles.contains(&sub_style_name),
-                    Error::<T>::NameAlreadyExists
-                );
-                sub_styles
-                    .try_push(sub_style_name)
-                    .map_err(|_| Error::<T>::StylesCapacity)?;
-                Ok(())
-            } else {
-                Err(Error::<T>::StyleNotFound.into())
-            }
-        })
+        let subs = MusicSubStyles::try_from(vec![sub_style_name])
+            .map_err(|_| Error::<T>::StylesCapacity)?;
+
+        Self::do_add_sub_styles(parent_style, subs, None, None)
     }
-}
+}
\ No newline at end of file
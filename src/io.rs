@@ -0,0 +1,254 @@
+//! Import and export of the styles tree in JSON and CSV, to bridge the taxonomies maintained
+//! outside of the chain, e.g. in spreadsheets, with the on-chain format
+//!
+//! Imports are validated with the same bounds and name rules as the extrinsics and stop at the
+//! first invalid entry, reported along with its line. Exports are deterministic: parent styles are
+//! written in the order of the tree and sub styles in their on-chain order.
+//!
+//! JSON follows the format of the genesis styles, see [`crate::GenesisStyle`]. CSV holds a
+//! `parent,sub` row per sub style and a `parent,` row per parent style without sub styles, fields
+//! with commas or quotes are quoted. The `parent,sub` header is written on export and optional on
+//! import.
+
+use super::*;
+use sp_std::fmt;
+
+/// Why a taxonomy couldn't be imported or exported, along with the line of the offending entry
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IoError {
+    /// Line of the offending entry, starting at 1
+    pub line: usize,
+    /// What is wrong with the entry
+    pub kind: IoErrorKind,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum IoErrorKind {
+    /// The input isn't well formed
+    Syntax(String),
+    /// The styles break the bounds or the name rules of the pallet
+    Invalid(GenesisError),
+    /// The parent style comes with a status or aliases, which the styles tree doesn't hold
+    Metadata(Vec<u8>),
+    /// The name can't be written in CSV, as it isn't valid UTF-8 or holds a line break
+    NotWritable(Vec<u8>),
+}
+
+impl fmt::Display for IoErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IoErrorKind::Syntax(message) => f.write_str(message),
+            IoErrorKind::Invalid(error) => write!(f, "{}", error),
+            IoErrorKind::Metadata(style) => write!(
+                f,
+                "the status and aliases of {} can't be imported",
+                String::from_utf8_lossy(style)
+            ),
+            IoErrorKind::NotWritable(name) => write!(
+                f,
+                "{} can't be written in CSV",
+                String::from_utf8_lossy(name)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl std::error::Error for IoError {}
+
+/// Read a styles tree from JSON
+pub fn from_json<T: Config>(input: &str) -> Result<MusicStyleDB, IoError> {
+    let mut styles = MusicStyleDB::new();
+    let mut rejected = None;
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+
+    crate::genesis::deserialize_with(&mut deserializer, |style| {
+        let result = if style.status.is_some() || !style.aliases.is_empty() {
+            Err(IoErrorKind::Metadata(style.name))
+        } else {
+            GenesisConfig::<T>::insert_style(&mut styles, &style).map_err(IoErrorKind::Invalid)
+        };
+        result.map_err(|kind| {
+            rejected = Some(kind.clone());
+            kind
+        })
+    })
+    .and_then(|_| deserializer.end())
+    .map_err(|error| IoError {
+        line: error.line(),
+        kind: rejected
+            .take()
+            .unwrap_or_else(|| IoErrorKind::Syntax(syntax_message(&error))),
+    })?;
+
+    Ok(styles)
+}
+
+/// Write a styles tree as pretty printed JSON
+pub fn to_json(styles: &MusicStyleDB) -> String {
+    let styles: Vec<GenesisStyle> = styles
+        .iter()
+        .map(|(parent, subs)| GenesisStyle::new(parent.to_vec(), subs.iter().map(|s| s.to_vec())))
+        .collect();
+
+    let mut output = Vec::new();
+    crate::genesis::serialize(&styles, &mut serde_json::Serializer::pretty(&mut output))
+        .expect("writing to memory can't fail");
+    String::from_utf8(output).expect("serde_json only writes UTF-8")
+}
+
+/// Read a styles tree from CSV
+pub fn from_csv<T: Config>(input: &str) -> Result<MusicStyleDB, IoError> {
+    let mut styles = MusicStyleDB::new();
+    let mut first_row = true;
+
+    for (index, row) in input.lines().enumerate() {
+        let line = index + 1;
+        let error = |kind| IoError { line, kind };
+        if row.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_row(row).map_err(|message| error(IoErrorKind::Syntax(message)))?;
+        let (parent, sub) = match fields.as_slice() {
+            [parent, sub]
+                if first_row
+                    && parent.eq_ignore_ascii_case("parent")
+                    && sub.eq_ignore_ascii_case("sub") =>
+            {
+                first_row = false;
+                continue;
+            }
+            [parent] => (parent, None),
+            [parent, sub] => (parent, Some(sub).filter(|sub| !sub.is_empty())),
+            _ => {
+                return Err(error(IoErrorKind::Syntax(format!(
+                    "expected a parent style and an optional sub style, found {} fields",
+                    fields.len()
+                ))))
+            }
+        };
+        first_row = false;
+        if parent.is_empty() {
+            return Err(error(IoErrorKind::Syntax("missing parent style".into())));
+        }
+
+        let invalid = |error: GenesisError| IoError {
+            line,
+            kind: IoErrorKind::Invalid(error),
+        };
+        let name = Pallet::<T>::to_bounded_style(parent.as_bytes().to_vec())
+            .map_err(|_| invalid(GenesisError::NameTooLong(parent.as_bytes().to_vec())))?;
+        if !styles.contains_key(&name) {
+            styles
+                .try_insert(name.clone(), Default::default())
+                .map_err(|_| invalid(GenesisError::TooManyStyles(parent.as_bytes().to_vec())))?;
+        }
+        if let (Some(sub), Some(subs)) = (sub, styles.get_mut(&name)) {
+            GenesisConfig::<T>::push_sub(parent.as_bytes(), subs, sub.as_bytes())
+                .map_err(invalid)?;
+        }
+    }
+
+    Ok(styles)
+}
+
+/// Write a styles tree as CSV, with a header
+pub fn to_csv(styles: &MusicStyleDB) -> Result<String, IoError> {
+    let mut output = String::from("parent,sub\n");
+    let mut line = 1;
+
+    for (parent, subs) in styles.iter() {
+        let rows: Vec<Option<&MusicStyleName>> = match subs.is_empty() {
+            true => vec![None],
+            false => subs.iter().map(Some).collect(),
+        };
+        for sub in rows {
+            line += 1;
+            output.push_str(&csv_field(parent, line)?);
+            output.push(',');
+            if let Some(sub) = sub {
+                output.push_str(&csv_field(sub, line)?);
+            }
+            output.push('\n');
+        }
+    }
+
+    Ok(output)
+}
+
+/// Message of a JSON error, without the position which is reported apart
+fn syntax_message(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(position) => message[..position].to_string(),
+        None => message,
+    }
+}
+
+/// Split a CSV row into its fields, unquoting the quoted ones
+fn parse_row(row: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut chars = row.chars().peekable();
+
+    loop {
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quoted field".into()),
+                }
+            }
+            match chars.next() {
+                Some(',') => fields.push(field),
+                None => {
+                    fields.push(field);
+                    return Ok(fields);
+                }
+                Some(c) => return Err(format!("unexpected {:?} after a quoted field", c)),
+            }
+        } else {
+            loop {
+                match chars.next() {
+                    Some(',') => break,
+                    Some('"') => return Err("unexpected quote in an unquoted field".into()),
+                    Some(c) => field.push(c),
+                    None => {
+                        fields.push(field);
+                        return Ok(fields);
+                    }
+                }
+            }
+            fields.push(field);
+        }
+    }
+}
+
+/// A name as a CSV field, quoted if it holds a comma or a quote
+fn csv_field(name: &[u8], line: usize) -> Result<String, IoError> {
+    let not_writable = || IoError {
+        line,
+        kind: IoErrorKind::NotWritable(name.to_vec()),
+    };
+    let name = sp_std::str::from_utf8(name).map_err(|_| not_writable())?;
+    if name.contains(['\n', '\r']) {
+        return Err(not_writable());
+    }
+
+    Ok(match name.contains([',', '"']) {
+        true => format!("\"{}\"", name.replace('"', "\"\"")),
+        false => name.to_string(),
+    })
+}
\ No newline at end of file
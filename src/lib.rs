@@ -9,17 +9,36 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+mod diff;
 mod functions;
+mod genesis;
 mod impls;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod migrations;
+pub mod runtime_api;
+#[cfg(feature = "default-taxonomy")]
+pub mod taxonomy;
+pub mod traits;
+mod types;
 pub mod weights;
 
 use allfeat_support::prelude::*;
-use allfeat_support::types::music::style::{MaxNameLength, MaxSubStyles};
+use allfeat_support::types::music::style::{
+    MaxNameLength, MaxParentStyles, MaxSubStyles, MusicSubStyles,
+};
+pub use diff::diff;
 use frame_support::pallet_prelude::*;
 use frame_system::pallet_prelude::*;
 pub use functions::*;
+pub use genesis::{GenesisMetadata, GenesisStyle};
+pub use impls::PauseFilter;
 pub use pallet::*;
-use sp_std::prelude::*;
+use sp_core::H256;
+use sp_runtime::traits::{Saturating, Zero};
+use sp_std::{prelude::*, vec};
+use traits::StyleChangeHandler;
+pub use types::*;
 pub use weights::WeightInfo;
 
 #[frame_support::pallet]
@@ -35,28 +54,341 @@ pub mod pallet {
         /// Who can manage a music style list
         type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
 
+        /// Who can pause and unpause the pallet in case of emergency
+        type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Who can replace the whole styles tree at once or through an import and revert its last
+        /// changes, the renames and removals of these bulk changes skipping the `EnactmentDelay`
+        type TaxonomyOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Number of blocks between the scheduling of a destructive change and its enactment
+        #[pallet::constant]
+        type EnactmentDelay: Get<BlockNumberFor<Self>>;
+
+        /// Handler notified of every change of the styles tree
+        type OnStyleChange: StyleChangeHandler;
+
+        /// Maximum number of style changes that can be enacted in a single block
+        #[pallet::constant]
+        type MaxScheduledPerBlock: Get<u32>;
+
+        /// Number of blocks of a rate limiting period
+        #[pallet::constant]
+        type RatePeriod: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of parent styles that can be created during a rate limiting period
+        #[pallet::constant]
+        type MaxNewParentStylesPerPeriod: Get<u32>;
+
+        /// Maximum number of sub styles that can be created during a rate limiting period
+        #[pallet::constant]
+        type MaxNewSubStylesPerPeriod: Get<u32>;
+
+        /// Number of taxonomy versions whose diff is kept to rebuild the past styles trees
+        #[pallet::constant]
+        type MaxHistory: Get<u32>;
+
+        /// Number of entries kept in the change log
+        #[pallet::constant]
+        type MaxChangeLogEntries: Get<u32>;
+
         /// Weight information for extrinsics in this pallet.
         type Weights: WeightInfo;
     }
 
+    /// The current storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(PhantomData<T>);
 
+    /// Sub styles of each parent style
+    #[pallet::storage]
+    pub(super) type ParentStyles<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, MusicStyleName, MusicSubStyles, OptionQuery>;
+
+    /// Parent styles holding each sub style (sub, parent), so a sub style can be looked up
+    /// without going through all the parent styles
+    #[pallet::storage]
+    pub(super) type SubStyleIndex<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        MusicStyleName,
+        Blake2_128Concat,
+        MusicStyleName,
+        (),
+        OptionQuery,
+    >;
+
+    /// Identifier of the next scheduled style change
+    #[pallet::storage]
+    pub(super) type NextChangeId<T: Config> = StorageValue<_, ChangeId, ValueQuery>;
+
+    /// Style changes waiting for their enactment
+    #[pallet::storage]
+    #[pallet::getter(fn scheduled_change)]
+    pub(super) type ScheduledChanges<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        ChangeId,
+        ScheduledChange<T::AccountId, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Version of the styles tree, bumped on every addition, rename or removal of a style
+    #[pallet::storage]
+    #[pallet::getter(fn taxonomy_version)]
+    pub(super) type TaxonomyVersion<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Change of the styles tree leading to each of the last `MaxHistory` taxonomy versions
+    #[pallet::storage]
+    pub(super) type History<T: Config> =
+        StorageMap<_, Twox64Concat, u64, TaxonomyDiff, OptionQuery>;
+
+    /// Taxonomy versions whose removal dropped the status, the references or the aliases of the
+    /// removed styles, they can't be reverted. Pruned along with the history
+    #[pallet::storage]
+    pub(super) type IrreversibleVersions<T: Config> =
+        StorageMap<_, Twox64Concat, u64, (), OptionQuery>;
+
+    /// Index of the next change log entry
+    #[pallet::storage]
+    pub(super) type NextLogIndex<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Last `MaxChangeLogEntries` changes of the styles, with who made them and why
+    #[pallet::storage]
+    #[pallet::getter(fn change_log)]
+    pub(super) type ChangeLog<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        u64,
+        ChangeLogEntry<T::AccountId, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Hash of the styles tree, cleared by every change of the tree and refreshed in the
+    /// `on_initialize` of the next block
+    #[pallet::storage]
+    pub(super) type TaxonomyHash<T: Config> = StorageValue<_, H256, OptionQuery>;
+
+    /// Style changes to enact at a given block
+    #[pallet::storage]
+    pub(super) type Agenda<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<ChangeId, T::MaxScheduledPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Changes which came due while the pallet was paused, by their position in the order they
+    /// are enacted once it is unpaused
+    #[pallet::storage]
+    pub(super) type DeferredChanges<T: Config> =
+        StorageMap<_, Twox64Concat, u32, ChangeId, OptionQuery>;
+
+    /// Positions of the first deferred change and past the last one
+    #[pallet::storage]
+    pub(super) type DeferredRange<T: Config> = StorageValue<_, (u32, u32), ValueQuery>;
+
+    /// Whether the whole styles tree is frozen
+    #[pallet::storage]
+    #[pallet::getter(fn is_frozen)]
+    pub(super) type Frozen<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Whether all the mutating calls of the pallet are paused
+    #[pallet::storage]
+    #[pallet::getter(fn is_paused)]
+    pub(super) type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Number of parent styles created during the current rate limiting period
+    #[pallet::storage]
+    pub(super) type NewParentStyles<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Number of sub styles created during the current rate limiting period
+    #[pallet::storage]
+    pub(super) type NewSubStyles<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Lifecycle status of the styles, a status applies to a name wherever it appears in the tree
+    /// and styles without status are active
+    #[pallet::storage]
+    pub(super) type StyleStatuses<T: Config> =
+        StorageMap<_, Blake2_128Concat, MusicStyleName, StyleStatus, OptionQuery>;
+
+    /// Number of references to the styles held by other pallets, a referenced style can't be
+    /// removed without forcing it
+    #[pallet::storage]
+    pub(super) type StyleReferenceCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, MusicStyleName, u32, ValueQuery>;
+
+    /// Other names resolving to a style (alias, style)
+    #[pallet::storage]
+    pub(super) type StyleAliases<T: Config> =
+        StorageMap<_, Blake2_128Concat, MusicStyleName, MusicStyleName, OptionQuery>;
+
+    /// Aliases of each style (style, alias), so they follow the renames and removals of the style
+    #[pallet::storage]
+    pub(super) type AliasIndex<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        MusicStyleName,
+        Blake2_128Concat,
+        MusicStyleName,
+        (),
+        OptionQuery,
+    >;
+
+    /// Styles that can't be renamed nor removed, locking a sub style also locks the structure of
+    /// its parent style while still allowing new sub styles beneath it
+    #[pallet::storage]
+    pub(super) type LockedStyles<T: Config> =
+        StorageMap<_, Blake2_128Concat, StyleRef, (), OptionQuery>;
+
+    /// Import being staged across several blocks
     #[pallet::storage]
-    #[pallet::getter(fn get_styles)]
-    pub(super) type Styles<T: Config> = StorageValue<_, MusicStyleDB, ValueQuery>;
+    #[pallet::getter(fn pending_import)]
+    pub(super) type Import<T: Config> = StorageValue<_, PendingImport<T::AccountId>, OptionQuery>;
+
+    /// Chunks of the import not staged yet
+    #[pallet::storage]
+    pub(super) type QueuedChunks<T: Config> =
+        StorageMap<_, Twox64Concat, u32, MusicStyleDB, OptionQuery>;
+
+    /// Shadow styles tree built from the staged chunks of the import, the styles tree is only
+    /// swapped for it once every chunk is staged
+    #[pallet::storage]
+    pub(super) type StagedStyles<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, MusicStyleName, MusicSubStyles, OptionQuery>;
+
+    /// Progress of `migrations::v1::LazyMigrateToV1`, the mutating calls are rejected while it
+    /// is set
+    #[pallet::storage]
+    pub(super) type StylesMigration<T: Config> =
+        StorageValue<_, migrations::v1::MigrationCursor, OptionQuery>;
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         /// A new music style has been added
-        StyleAdded(Vec<u8>),
-        /// A new sub style has been added to parent
-        SubStyleAdded(Vec<u8>),
-        /// A style name has been updated (old, new)
-        StyleNameUpdated(Vec<u8>, Vec<u8>),
-        /// A sub-style name has been updated (old, new)
-        SubStyleNameUpdated(Vec<u8>, Vec<u8>),
+        StyleAdded {
+            name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A new sub style has been added to a parent style
+        SubStyleAdded {
+            parent: MusicStyleName,
+            name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A style name has been updated
+        StyleNameUpdated {
+            old_name: MusicStyleName,
+            new_name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A sub style name has been updated
+        SubStyleNameUpdated {
+            parent: MusicStyleName,
+            old_name: MusicStyleName,
+            new_name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A style and its sub styles have been removed
+        StyleRemoved {
+            name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A sub style has been removed from its parent style
+        SubStyleRemoved {
+            parent: MusicStyleName,
+            name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A destructive change has been scheduled
+        StyleChangeScheduled {
+            id: ChangeId,
+            change: StyleChange,
+            when: BlockNumberFor<T>,
+            who: Option<T::AccountId>,
+        },
+        /// A scheduled change has been cancelled
+        StyleChangeCancelled {
+            id: ChangeId,
+            who: Option<T::AccountId>,
+        },
+        /// A scheduled change has been enacted
+        StyleChangeEnacted { id: ChangeId },
+        /// A scheduled change could not be enacted at its enactment block
+        StyleChangeFailed { id: ChangeId, error: DispatchError },
+        /// The styles tree has been frozen or unfrozen
+        FrozenUpdated {
+            frozen: bool,
+            who: Option<T::AccountId>,
+        },
+        /// A style has been locked or unlocked
+        StyleLockUpdated {
+            style: StyleRef,
+            locked: bool,
+            who: Option<T::AccountId>,
+        },
+        /// All the mutating calls have been paused
+        Paused { who: Option<T::AccountId> },
+        /// The mutating calls have been unpaused
+        Unpaused { who: Option<T::AccountId> },
+        /// The lifecycle status of a style has been updated
+        StyleStatusUpdated {
+            name: MusicStyleName,
+            status: StyleStatus,
+            who: Option<T::AccountId>,
+        },
+        /// A sub style has been moved under another parent style
+        SubStyleMoved {
+            parent: MusicStyleName,
+            new_parent: MusicStyleName,
+            name: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A taxonomy import has been queued, it is staged in the idle time of the next blocks
+        ImportQueued {
+            chunks: u32,
+            who: Option<T::AccountId>,
+        },
+        /// A chunk of the import has been staged
+        ImportProgress { staged: u32, total: u32 },
+        /// Every chunk has been staged and the styles tree has been replaced by the import
+        ImportCompleted {
+            version: u64,
+            who: Option<T::AccountId>,
+        },
+        /// The import has been dropped, the styles tree is unchanged
+        ImportFailed { error: DispatchError },
+        /// The import has been cancelled before its completion
+        ImportCancelled { who: Option<T::AccountId> },
+        /// The styles tree has been fully migrated by `migrations::v1::LazyMigrateToV1`
+        StylesMigrated,
+        /// A scheduled change came due while the pallet was paused, it is enacted once the pallet
+        /// is unpaused
+        StyleChangeDeferred { id: ChangeId },
+        /// The sub styles of a parent style have been reordered by a taxonomy replacement
+        SubStylesReordered {
+            parent: MusicStyleName,
+            who: Option<T::AccountId>,
+            version: u64,
+        },
+        /// A parent style of the old styles tree can't be decoded by
+        /// `migrations::v1::LazyMigrateToV1`, the mutating calls stay rejected
+        StylesMigrationFailed { remaining: u32 },
+        /// The lazy migration of the styles tree has been resumed by root from the given offset
+        /// in the chunks of the old styles tree
+        StylesMigrationResumed { offset: u32, remaining: u32 },
     }
 
     #[pallet::error]
@@ -69,13 +401,104 @@ pub mod pallet {
         StyleNotFound,
         /// The music styles vec is full
         StylesCapacity,
+        /// Too many changes are already scheduled for the enactment block
+        TooManyScheduledChanges,
+        /// No scheduled change matches the given id
+        ChangeNotFound,
+        /// The styles tree or the targeted style is frozen
+        StyleFrozen,
+        /// The pallet is paused
+        PalletPaused,
+        /// Too many styles have been created during the current rate limiting period
+        RateLimited,
+        /// A deprecated style can't be replaced by itself nor by an unexisting style
+        InvalidReplacement,
+        /// The style is still referenced by other pallets
+        StyleInUse,
+        /// The style has no reference to release
+        NotReferenced,
+        /// The changes can't be reverted, their history is not kept anymore or the styles tree
+        /// doesn't match it
+        NotRevertible,
+        /// Only sub styles can be moved, under another existing parent style
+        InvalidMove,
+        /// An import is already pending, or the references are frozen until it completes
+        ImportInProgress,
+        /// No import is pending
+        NoImport,
+        /// An import needs at least one chunk
+        EmptyImport,
+        /// The styles tree is being migrated
+        MigrationInProgress,
+        /// Draft and retired styles can't be referenced
+        StyleNotUsable,
+        /// The import needs more weight than a whole block to replace the styles tree
+        ImportTooLarge,
+        /// The styles tree is not being migrated
+        NoMigration,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            let mut weight = Weight::zero();
+
+            // A new rate limiting period starts
+            if (now % T::RatePeriod::get()).is_zero() {
+                <NewParentStyles<T>>::kill();
+                <NewSubStyles<T>>::kill();
+                weight.saturating_accrue(T::DbWeight::get().writes(2));
+            }
+
+            // The changes coming due while the pallet is paused or its styles tree is migrated are
+            // queued until the end of it, then enacted within the per block limit
+            weight.saturating_accrue(T::DbWeight::get().reads(2));
+            if Self::is_paused() || Self::is_migrating() {
+                let count = Self::defer_agenda(now);
+                weight.saturating_accrue(T::Weights::defer_changes(count));
+            } else {
+                weight.saturating_accrue(Self::enact_agenda(now));
+            }
+
+            // The styles tree is hashed at most once per block, after the changes of the
+            // previous block and the enacted ones
+            weight.saturating_add(Self::refresh_taxonomy_hash())
+        }
+
+        fn on_idle(_now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let weight = T::DbWeight::get().reads(1);
+            if !remaining_weight.all_gte(weight) {
+                return Weight::zero();
+            }
+
+            // The import waits for the end of the migration
+            let limit = remaining_weight.saturating_sub(weight);
+            match <StylesMigration<T>>::get() {
+                Some(cursor) => {
+                    weight.saturating_add(migrations::v1::migrate_step::<T>(cursor, limit))
+                }
+                None => weight.saturating_add(Self::process_import(limit)),
+            }
+        }
+
+        fn integrity_test() {
+            assert!(
+                !T::EnactmentDelay::get().is_zero(),
+                "EnactmentDelay must be at least one block"
+            );
+            assert!(
+                !T::RatePeriod::get().is_zero(),
+                "RatePeriod must be at least one block"
+            );
+        }
     }
 
     #[pallet::genesis_config]
     #[derive(frame_support::DefaultNoBound)]
     pub struct GenesisConfig<T: Config> {
-        /// The existing music styles at the genesis
-        pub styles: Vec<(Vec<u8>, Vec<Vec<u8>>)>,
+        /// The existing music styles at the genesis, along with their metadata
+        #[serde(with = "crate::genesis")]
+        pub styles: Vec<GenesisStyle>,
         // Note: Use phantom data because we need a Generic in the GenesisConfig
         pub phantom: PhantomData<T>,
     }
@@ -83,16 +506,128 @@ pub mod pallet {
     #[pallet::genesis_build]
     impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
         fn build(&self) {
+            let (styles, metadata) = self
+                .build_styles()
+                .and_then(|styles| {
+                    self.build_metadata(&styles)
+                        .map(|metadata| (styles, metadata))
+                })
+                .unwrap_or_else(|error| panic!("Invalid music styles genesis: {}", error));
+
+            Pallet::<T>::put_styles(styles);
+            for (name, status) in metadata.statuses {
+                <StyleStatuses<T>>::insert(name, status);
+            }
+            for (alias, name) in metadata.aliases {
+                Pallet::<T>::put_alias(alias, name);
+            }
+            <TaxonomyHash<T>>::put(Pallet::<T>::hash_styles());
+        }
+    }
+
+    impl<T: Config> GenesisConfig<T> {
+        /// Validate all the genesis styles with the same policy as the extrinsics and build the
+        /// styles tree from them, the first invalid entry is reported along with its name
+        pub fn build_styles(&self) -> Result<MusicStyleDB, GenesisError> {
             let mut styles = MusicStyleDB::new();
 
-            for (input_name, input_sub_styles) in &self.styles {
-                let parent = Pallet::<T>::to_bounded_style(input_name.clone()).unwrap();
-                let subs = Pallet::<T>::to_bounded_sub_styles(input_sub_styles.clone()).unwrap();
+            for style in &self.styles {
+                Self::insert_style(&mut styles, style)?;
+            }
 
-                styles.try_insert(parent, subs).unwrap();
+            Ok(styles)
+        }
+
+        /// Validate a parent style and its sub styles, then add them to the styles tree
+        pub(crate) fn insert_style(
+            styles: &mut MusicStyleDB,
+            style: &GenesisStyle,
+        ) -> Result<(), GenesisError> {
+            let parent = Pallet::<T>::to_bounded_style(style.name.clone())
+                .map_err(|_| GenesisError::NameTooLong(style.name.clone()))?;
+            ensure!(
+                !styles.contains_key(&parent),
+                GenesisError::DuplicateStyle(style.name.clone())
+            );
+
+            let mut subs = MusicSubStyles::default();
+            for input_sub in &style.subs {
+                Self::push_sub(&style.name, &mut subs, input_sub)?;
             }
 
-            <Styles<T>>::put(styles);
+            styles
+                .try_insert(parent, subs)
+                .map_err(|_| GenesisError::TooManyStyles(style.name.clone()))?;
+
+            Ok(())
+        }
+
+        /// Validate a sub style and append it to the sub styles of its parent style
+        pub(crate) fn push_sub(
+            parent: &[u8],
+            subs: &mut MusicSubStyles,
+            input_sub: &[u8],
+        ) -> Result<(), GenesisError> {
+            let sub = Pallet::<T>::to_bounded_style(input_sub.to_vec())
+                .map_err(|_| GenesisError::NameTooLong(input_sub.to_vec()))?;
+            let new_sub = BoundedVec::truncate_from(vec![sub]);
+
+            Pallet::<T>::checked_add_subs(subs, &new_sub).map_err(|error| {
+                if error == Error::<T>::NameAlreadyExists.into() {
+                    GenesisError::DuplicateSubStyle(parent.to_vec(), input_sub.to_vec())
+                } else {
+                    GenesisError::TooManySubStyles(parent.to_vec())
+                }
+            })
+        }
+
+        /// Validate the statuses and the aliases of the genesis styles against their tree, the
+        /// first invalid entry is reported along with its name
+        pub fn build_metadata(
+            &self,
+            styles: &MusicStyleDB,
+        ) -> Result<GenesisMetadata, GenesisError> {
+            let mut metadata = GenesisMetadata::default();
+
+            for style in &self.styles {
+                let name = Pallet::<T>::to_bounded_style(style.name.clone())
+                    .map_err(|_| GenesisError::NameTooLong(style.name.clone()))?;
+
+                if let Some(status) = &style.status {
+                    if let StyleStatus::Deprecated(Some(replacement)) = status {
+                        ensure!(
+                            *replacement != name && Pallet::<T>::contains_name(styles, replacement),
+                            GenesisError::InvalidReplacement(style.name.clone())
+                        );
+                    }
+                    metadata.statuses.push((name.clone(), status.clone()));
+                }
+
+                for input_alias in &style.aliases {
+                    let alias = Pallet::<T>::to_bounded_style(input_alias.clone())
+                        .map_err(|_| GenesisError::NameTooLong(input_alias.clone()))?;
+                    ensure!(
+                        !Pallet::<T>::contains_name(styles, &alias)
+                            && !metadata.aliases.iter().any(|(a, _)| *a == alias),
+                        GenesisError::AliasConflict(input_alias.clone())
+                    );
+                    metadata.aliases.push((alias, name.clone()));
+                }
+            }
+
+            Ok(metadata)
+        }
+
+        /// Genesis holding the curated taxonomy bundled with the `default-taxonomy` feature
+        #[cfg(feature = "default-taxonomy")]
+        pub fn with_default_taxonomy() -> Self {
+            Self {
+                styles: crate::taxonomy::DEFAULT_TAXONOMY
+                    .iter()
+                    .map(|(name, subs)| GenesisStyle::new(*name, subs.iter().copied()))
+                    .collect(),
+                phantom: Default::default(),
+            }
         }
     }
 
@@ -104,151 +639,360 @@ pub mod pallet {
         #[pallet::weight(T::Weights::add_style(
             <MaxNameLength as Get<u32>>::get(),
             <MaxSubStyles as Get<u32>>::get()
-        ))]
+        ).saturating_add(Pallet::<T>::notifications_weight(
+            <MaxSubStyles as Get<u32>>::get() + 1
+        )))]
         pub fn add_style(
             origin: OriginFor<T>,
             name: Vec<u8>,
             sub: Option<Vec<Vec<u8>>>,
+            reason: Option<ChangeReason>,
         ) -> DispatchResult {
             T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
 
-            let mut styles: MusicStyleDB = Self::get_styles();
-
-            let parent_name = Self::to_bounded_style(name.clone())?;
-
-            if styles.contains_key(&parent_name) {
-                return Err(Error::<T>::NameAlreadyExists)?;
-            } else {
-                styles
-                    .try_insert(parent_name.clone(), Default::default())
-                    .map_err(|_| Error::<T>::StylesCapacity)?;
-            }
-
-            match sub {
-                Some(ref subs) => {
-                    let bounded_subs = Self::to_bounded_sub_styles(subs.clone())?;
-
-                    Self::checked_add_subs(&mut styles, bounded_subs, parent_name)?;
-                }
+            let name = Self::to_bounded_style(name)?;
+            let subs = match sub {
+                Some(subs) => Self::to_bounded_sub_styles(subs)?,
                 // Not adding subs
-                None => (),
-            }
-
-            <Styles<T>>::put(styles);
-
-            // Emitting events
-            Self::deposit_event(Event::StyleAdded(name));
-            match sub {
-                Some(subs) => {
-                    for sub in subs {
-                        Self::deposit_event(Event::SubStyleAdded(sub))
-                    }
-                }
-                None => (),
-            }
+                None => Default::default(),
+            };
 
-            Ok(())
+            Self::do_add_style(name, subs, who, reason)
         }
 
         #[pallet::call_index(1)]
         #[pallet::weight(T::Weights::add_sub_style(
             <MaxNameLength as Get<u32>>::get(),
             <MaxSubStyles as Get<u32>>::get()
-        ))]
+        ).saturating_add(Pallet::<T>::notifications_weight(
+            <MaxSubStyles as Get<u32>>::get()
+        )))]
         pub fn add_sub_style(
             origin: OriginFor<T>,
             parent_style: Vec<u8>,
             subs_style: Vec<Vec<u8>>,
+            reason: Option<ChangeReason>,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+
+            let parent = Self::to_bounded_style(parent_style)?;
+            let subs = Self::to_bounded_sub_styles(subs_style)?;
+
+            Self::do_add_sub_styles(parent, subs, who, reason)
+        }
+
+        /// Schedule the renaming of a style, or of one of its sub styles when `sub_style` is
+        /// given. The change is enacted `EnactmentDelay` blocks later
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::Weights::update_style_name())]
+        pub fn update_style_name(
+            origin: OriginFor<T>,
+            parent_style: Vec<u8>,
+            sub_style: Option<Vec<u8>>,
+            new_name: Vec<u8>,
+            reason: Option<ChangeReason>,
         ) -> DispatchResult {
             T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
 
-            let mut styles: MusicStyleDB = Self::get_styles();
+            let style = Self::to_style_ref(parent_style, sub_style)?;
+            let new_name = Self::to_bounded_style(new_name)?;
+
+            Self::schedule_change(StyleChange::Rename { style, new_name }, who, reason)
+        }
+
+        /// Schedule the removal of a style (and its sub styles), or of one of its sub styles
+        /// when `sub_style` is given. The change is enacted `EnactmentDelay` blocks later
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::Weights::remove_style())]
+        pub fn remove_style(
+            origin: OriginFor<T>,
+            parent_style: Vec<u8>,
+            sub_style: Option<Vec<u8>>,
+            reason: Option<ChangeReason>,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
+
+            let style = Self::to_style_ref(parent_style, sub_style)?;
+
+            Self::schedule_change(
+                StyleChange::Remove {
+                    style,
+                    force: false,
+                },
+                who,
+                reason,
+            )
+        }
+
+        /// Cancel a scheduled change before its enactment
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::Weights::cancel_change())]
+        pub fn cancel_change(origin: OriginFor<T>, id: ChangeId) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
+
+            let scheduled = <ScheduledChanges<T>>::take(id).ok_or(Error::<T>::ChangeNotFound)?;
+            <Agenda<T>>::mutate(scheduled.when, |agenda| agenda.retain(|i| *i != id));
+
+            Self::deposit_event(Event::StyleChangeCancelled { id, who });
+
+            Ok(())
+        }
+
+        /// Freeze or unfreeze the whole styles tree, no style can be added, renamed or removed
+        /// while it is frozen
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::Weights::set_frozen())]
+        pub fn set_frozen(origin: OriginFor<T>, frozen: bool) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
+
+            <Frozen<T>>::put(frozen);
+            Self::deposit_event(Event::FrozenUpdated { frozen, who });
+
+            Ok(())
+        }
+
+        /// Lock or unlock a style, or one of its sub styles when `sub_style` is given
+        /// A locked style can't be renamed nor removed but new sub styles can still be added to it
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::Weights::set_style_lock())]
+        pub fn set_style_lock(
+            origin: OriginFor<T>,
+            parent_style: Vec<u8>,
+            sub_style: Option<Vec<u8>>,
+            locked: bool,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
 
-            let bounded_parent_style = Self::to_bounded_style(parent_style)?;
+            let style = Self::to_style_ref(parent_style, sub_style)?;
 
-            if !styles.contains_key(&bounded_parent_style) {
-                return Err(Error::<T>::StyleNotFound)?;
+            if locked {
+                Self::ensure_style_exists(&Self::styles_of([style.parent()]), &style)?;
+                <LockedStyles<T>>::insert(&style, ());
+            } else {
+                <LockedStyles<T>>::remove(&style);
             }
 
-            let bounded_subs = Self::to_bounded_sub_styles(subs_style.clone())?;
+            Self::deposit_event(Event::StyleLockUpdated { style, locked, who });
 
-            Self::checked_add_subs(&mut styles, bounded_subs, bounded_parent_style)?;
+            Ok(())
+        }
 
-            <Styles<T>>::put(styles);
+        /// Pause all the mutating calls of the pallet, reads stay available
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::Weights::pause())]
+        pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+
+            <Paused<T>>::put(true);
+            Self::deposit_event(Event::Paused { who });
+
+            Ok(())
+        }
+
+        /// Unpause the mutating calls of the pallet
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::Weights::unpause())]
+        pub fn unpause(origin: OriginFor<T>) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+
+            <Paused<T>>::put(false);
+            Self::deposit_event(Event::Unpaused { who });
+
+            Ok(())
+        }
 
-            for sub in subs_style {
-                Self::deposit_event(Event::SubStyleAdded(sub))
+        /// Update the lifecycle status of a style
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::Weights::set_style_status())]
+        pub fn set_style_status(
+            origin: OriginFor<T>,
+            name: Vec<u8>,
+            status: StyleStatus,
+            reason: Option<ChangeReason>,
+        ) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
+
+            let name = Self::to_bounded_style(name)?;
+            ensure!(Self::name_exists(&name), Error::<T>::StyleNotFound);
+
+            if let StyleStatus::Deprecated(Some(ref replacement)) = status {
+                ensure!(
+                    *replacement != name && Self::name_exists(replacement),
+                    Error::<T>::InvalidReplacement
+                );
             }
 
+            <StyleStatuses<T>>::insert(&name, &status);
+            Self::log_change(
+                LogOperation::StatusUpdated {
+                    name: name.clone(),
+                    status: status.clone(),
+                },
+                who.clone(),
+                reason,
+            );
+            Self::deposit_event(Event::StyleStatusUpdated { name, status, who });
+
             Ok(())
         }
 
-        /*#[pallet::weight(0)]
-        pub fn update_style_name(
+        /// Schedule the removal of a style like `remove_style`, even if the removed styles are
+        /// still referenced by other pallets. They are notified through `OnStyleChange`
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::Weights::remove_style())]
+        pub fn force_remove_style(
             origin: OriginFor<T>,
-            id: H256,
-            new_name: Vec<u8>,
+            parent_style: Vec<u8>,
+            sub_style: Option<Vec<u8>>,
+            reason: Option<ChangeReason>,
         ) -> DispatchResult {
             T::AdminOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
 
-            let bounded_name = Self::try_into_bounded_name(new_name.clone())?;
-            let mut styles = <Styles<T>>::get();
-            let style_kind = Self::get_style(id, &styles);
+            let style = Self::to_style_ref(parent_style, sub_style)?;
 
-            match style_kind {
-                StyleKind::MainStyle(style) => {
-                    // Check if the name is free at main styles level
-                    if styles.iter().find(|s| s.name == bounded_name).is_some() {
-                        return Err(Error::<T>::NameAlreadyExists)?;
-                    }
+            Self::schedule_change(StyleChange::Remove { style, force: true }, who, reason)
+        }
 
-                    // Get and mutate style
-                    let mut style = styles
-                        .iter_mut()
-                        .find(|s| s.id == style.id)
-                        .ok_or_else(|| Error::<T>::StyleNotFound)?;
+        /// Revert the last `count` changes of the styles tree by applying their inverse in reverse
+        /// order. Every reverting change is recorded as a new taxonomy version and emits the
+        /// usual events, the whole call fails if one of the changes can't be reverted. At most
+        /// `MaxHistory` changes can be reverted, and not the removals which dropped the status,
+        /// the references or the aliases of a style
+        ///
+        /// Like `set_taxonomy`, the reverting renames and removals are applied right away rather
+        /// than after `EnactmentDelay` and the restored styles are not rate limited, hence the
+        /// `TaxonomyOrigin`
+        #[pallet::call_index(11)]
+        #[pallet::weight({
+            let count = (*count).min(T::MaxHistory::get());
+            T::Weights::revert_changes(count).saturating_add(Pallet::<T>::notifications_weight(
+                count.saturating_mul(<MaxSubStyles as Get<u32>>::get() + 1)
+            ))
+        })]
+        pub fn revert_changes(
+            origin: OriginFor<T>,
+            count: u32,
+            reason: Option<ChangeReason>,
+        ) -> DispatchResult {
+            T::TaxonomyOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
 
-                    let old_style = style.clone();
-                    style.name = bounded_name;
-                    let new_style = style.clone();
+            Self::do_revert_changes(count, who, reason)
+        }
 
-                    <Styles<T>>::put(styles);
-                    Self::deposit_event(Event::StyleNameUpdated(old_style, new_style));
-                }
-                StyleKind::SubStyle(sub_style) => {
-                    // Get the parent style
-                    let parent_style = styles
-                        .iter_mut()
-                        .find(|s| s.id == sub_style.parent_id)
-                        .ok_or_else(|| Error::<T>::StyleNotFound)?;
-
-                    // check is the new name is not already used in sub styles.
-                    if let Some(_) = parent_style
-                        .sub_styles
-                        .iter()
-                        .find(|s| s.name == bounded_name)
-                    {
-                        return Err(Error::<T>::NameAlreadyExists)?;
-                    }
+        /// Replace the whole styles tree in one call. The changes from the current tree are
+        /// computed by [`diff`](crate::diff) and the new tree is written at once, each change
+        /// emitting its usual event and being recorded as a new taxonomy version, as well as the
+        /// reordering of sub styles. The new styles are not rate limited. The call fails without
+        /// changing anything if one of the changes can't be applied, e.g. the removal of a
+        /// referenced style
+        ///
+        /// The renames and removals are applied right away rather than after `EnactmentDelay`,
+        /// hence the dedicated `TaxonomyOrigin`: it is meant for a governance referendum whose own
+        /// enactment period leaves the dependent pallets time to prepare
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::Weights::set_taxonomy(
+            Pallet::<T>::styles_count(new),
+            <MaxParentStyles as Get<u32>>::get()
+        ).saturating_add(Pallet::<T>::notifications_weight(
+            Pallet::<T>::styles_count(new).saturating_add(
+                <MaxParentStyles as Get<u32>>::get()
+                    .saturating_mul(<MaxSubStyles as Get<u32>>::get() + 1)
+            )
+        )))]
+        pub fn set_taxonomy(
+            origin: OriginFor<T>,
+            new: MusicStyleDB,
+            reason: Option<ChangeReason>,
+        ) -> DispatchResult {
+            T::TaxonomyOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
 
-                    // Get and mutate style
-                    let sub_style = parent_style
-                        .sub_styles
-                        .iter_mut()
-                        .find(|s| s.id == sub_style.id)
-                        .ok_or_else(|| Error::<T>::StyleNotFound)?;
+            Self::do_set_taxonomy(new, who, reason)
+        }
 
-                    let old_sub_style = sub_style.clone();
-                    sub_style.name = bounded_name;
-                    let new_sub_style = sub_style.clone();
+        /// Queue the import of a taxonomy too large for a single block. The chunks are staged one
+        /// after the other in the idle time of the next blocks, then the styles tree is replaced
+        /// by the staged one in a single step like `set_taxonomy` would, once a block has enough
+        /// idle weight for it. A parent style can be spread over several chunks
+        ///
+        /// Like `set_taxonomy`, the import skips the `EnactmentDelay` and is gated by the
+        /// `TaxonomyOrigin`. The references of the styles can't change until it completes
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::Weights::queue_import(
+            chunks.len() as u32,
+            chunks.iter().map(Pallet::<T>::styles_count).sum()
+        ))]
+        pub fn queue_import(
+            origin: OriginFor<T>,
+            chunks: ImportChunks,
+            reason: Option<ChangeReason>,
+        ) -> DispatchResult {
+            T::TaxonomyOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
 
-                    <Styles<T>>::put(styles);
-                    Self::deposit_event(Event::SubStyleNameUpdated(old_sub_style, new_sub_style));
-                }
-                StyleKind::None => Err(Error::<T>::StyleNotFound)?,
-            };
+            Self::do_queue_import(chunks, who, reason)
+        }
+
+        /// Cancel the pending import and drop its staged chunks, the styles tree is unchanged
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::Weights::cancel_import(
+            <MaxImportChunks as Get<u32>>::get(),
+            <MaxParentStyles as Get<u32>>::get()
+        ))]
+        pub fn cancel_import(origin: OriginFor<T>) -> DispatchResult {
+            T::TaxonomyOrigin::ensure_origin(origin.clone())?;
+            let who = ensure_signed(origin).ok();
+            Self::ensure_not_paused()?;
+
+            ensure!(<Import<T>>::exists(), Error::<T>::NoImport);
+            Self::clear_import();
+            Self::deposit_event(Event::ImportCancelled { who });
+
+            Ok(())
+        }
+
+        /// Resume the lazy migration of the styles tree from the parent style at `offset` in the
+        /// `OldStylesChunks`, with `remaining` parent styles left to migrate. It restarts a
+        /// migration stopped by a parent style or a length prefix which couldn't be decoded, once
+        /// the chunks are fixed through the raw storage if needed: skipping the undecodable bytes
+        /// drops them, and no remaining parent style ends the migration with the parent styles
+        /// migrated so far
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::Weights::resume_styles_migration())]
+        pub fn resume_styles_migration(
+            origin: OriginFor<T>,
+            offset: u32,
+            remaining: u32,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let mut cursor = <StylesMigration<T>>::get().ok_or(Error::<T>::NoMigration)?;
+            cursor.offset = offset;
+            cursor.remaining = remaining;
+            cursor.failed = false;
+            <StylesMigration<T>>::put(cursor);
+            Self::deposit_event(Event::StylesMigrationResumed { offset, remaining });
 
             Ok(())
-        }*/
+        }
     }
-}
+}
\ No newline at end of file
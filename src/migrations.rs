@@ -0,0 +1,453 @@
+//! Storage migrations of the music styles pallet
+
+use super::*;
+use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+/// Storage layout before the version 1
+pub mod v0 {
+    use super::*;
+
+    /// The whole styles tree, stored as a single value
+    #[frame_support::storage_alias]
+    pub type Styles<T: Config> = StorageValue<Pallet<T>, MusicStyleDB, ValueQuery>;
+}
+
+pub mod v1 {
+    use super::*;
+    use codec::Compact;
+
+    /// A style change before the version 1, the removals couldn't be forced
+    #[derive(Encode, Decode)]
+    pub enum OldStyleChange {
+        Rename {
+            style: StyleRef,
+            new_name: MusicStyleName,
+        },
+        Remove {
+            style: StyleRef,
+        },
+    }
+
+    impl From<OldStyleChange> for StyleChange {
+        fn from(old: OldStyleChange) -> Self {
+            match old {
+                OldStyleChange::Rename { style, new_name } => {
+                    StyleChange::Rename { style, new_name }
+                }
+                OldStyleChange::Remove { style } => StyleChange::Remove {
+                    style,
+                    force: false,
+                },
+            }
+        }
+    }
+
+    /// A scheduled change before the version 1
+    #[derive(Encode, Decode)]
+    pub struct OldScheduledChange<BlockNumber> {
+        pub change: OldStyleChange,
+        pub when: BlockNumber,
+    }
+
+    /// Style changes waiting for their enactment, as stored by the version 1
+    #[frame_support::storage_alias]
+    pub type ScheduledChanges<T: Config> = StorageMap<
+        Pallet<T>,
+        Twox64Concat,
+        ChangeId,
+        v2::OldScheduledChange<BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Re-encode the pending changes with the `force` flag of the removals, the removals
+    /// scheduled before the version 1 are not forced. Returns the number of migrated changes
+    pub fn migrate_scheduled_changes<T: Config>() -> u64 {
+        let mut count = 0u64;
+        ScheduledChanges::<T>::translate::<OldScheduledChange<BlockNumberFor<T>>, _>(|_, old| {
+            count += 1;
+            Some(v2::OldScheduledChange {
+                change: old.change.into(),
+                when: old.when,
+            })
+        });
+
+        count
+    }
+
+    /// Move the styles tree from a single value to the `ParentStyles` map and build the
+    /// `SubStyleIndex`, then migrate the pending changes with `migrate_scheduled_changes`
+    pub struct MigrateToV1<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() >= 1 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let styles = v0::Styles::<T>::take();
+            let parents = styles.len() as u64;
+            let subs: u64 = styles.values().map(|subs| subs.len() as u64).sum();
+
+            Pallet::<T>::put_styles(styles);
+            let changes = migrate_scheduled_changes::<T>();
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            // Parent styles and their counter, sub styles index, scheduled changes, old value and
+            // version
+            T::DbWeight::get().reads_writes(changes + 3, parents + subs + changes + 3)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            Ok((
+                v0::Styles::<T>::get(),
+                ScheduledChanges::<T>::iter_keys().count() as u32,
+            )
+                .encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let (styles, changes) = <(MusicStyleDB, u32)>::decode(&mut &state[..])
+                .map_err(|_| "the pre upgrade state can't be decoded")?;
+
+            ensure!(
+                Pallet::<T>::get_styles() == styles,
+                "the styles tree changed during the migration"
+            );
+            ensure!(
+                ScheduledChanges::<T>::iter().count() as u32 == changes,
+                "some scheduled changes have been lost during the migration"
+            );
+            ensure!(
+                !v0::Styles::<T>::exists(),
+                "the old styles tree has not been removed"
+            );
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() == 1,
+                "the storage version has not been updated"
+            );
+
+            Ok(())
+        }
+    }
+
+    frame_support::parameter_types! {
+        /// Length of the chunks of the encoded old styles tree, an encoded parent style spans
+        /// two chunks at most
+        pub ChunkLength: u32 = <(MusicStyleName, MusicSubStyles)>::max_encoded_len() as u32;
+    }
+
+    /// A chunk of the encoded old styles tree
+    pub type OldStylesChunk = BoundedVec<u8, ChunkLength>;
+
+    /// The encoded parent styles of the old styles tree, without its length prefix, split in
+    /// chunks so each step of `LazyMigrateToV1` only reads the ones it decodes. The prefix is
+    /// kept when it can't be decoded
+    #[frame_support::storage_alias]
+    pub type OldStylesChunks<T: Config> =
+        StorageMap<Pallet<T>, Twox64Concat, u32, OldStylesChunk, OptionQuery>;
+
+    /// Progress of the lazy migration of the styles tree to the version 1
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct MigrationCursor {
+        /// Offset of the next parent style in the chunks of the encoded old styles tree
+        pub offset: u32,
+        /// Number of parent styles left to migrate
+        pub remaining: u32,
+        /// Maximum number of parent styles migrated per block
+        pub step: u32,
+        /// Whether the next parent style or the length prefix of the old styles tree couldn't be
+        /// decoded. The migration is stopped and the mutating calls stay rejected until it is
+        /// resumed by `resume_styles_migration`
+        pub failed: bool,
+    }
+
+    /// Start moving the styles tree from a single value to the `ParentStyles` map, up to `Step`
+    /// parent styles per block, for the chains whose tree is too large for `MigrateToV1`
+    ///
+    /// The old value is split in `OldStylesChunks` during the upgrade, the parent styles are then
+    /// decoded from the chunks one after the other in the idle time of the next blocks, as many
+    /// as the remaining weight allows. The pending changes, bounded by the agenda, are migrated
+    /// right away. The storage version is bumped right away so the following migrations can run,
+    /// but every mutating call is rejected and the scheduled changes are deferred until the last
+    /// parent style is migrated. An old value whose length prefix can't be decoded is split
+    /// whole and the migration starts failed, nothing being migrated until it is resumed.
+    pub struct LazyMigrateToV1<T, Step>(PhantomData<(T, Step)>);
+
+    impl<T: Config, Step: Get<u32>> OnRuntimeUpgrade for LazyMigrateToV1<T, Step> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() >= 1 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let key = v0::Styles::<T>::hashed_key();
+            let (mut size, mut chunks) = (0, 0);
+            if let Some(old) = frame_support::storage::unhashed::get_raw(&key) {
+                let mut input = &old[..];
+                let parents = Compact::<u32>::decode(&mut input)
+                    .map(|parents| parents.0)
+                    .ok();
+                if parents.is_none() {
+                    input = &old[..];
+                }
+                for (index, chunk) in input.chunks(ChunkLength::get() as usize).enumerate() {
+                    OldStylesChunks::<T>::insert(
+                        index as u32,
+                        BoundedVec::truncate_from(chunk.to_vec()),
+                    );
+                    chunks += 1;
+                }
+                size = old.len() as u64;
+
+                frame_support::storage::unhashed::kill(&key);
+                let cursor = MigrationCursor {
+                    offset: 0,
+                    remaining: parents.unwrap_or_default(),
+                    step: Step::get().max(1),
+                    failed: parents.is_none(),
+                };
+                if cursor.failed {
+                    Pallet::<T>::deposit_event(Event::StylesMigrationFailed { remaining: 0 });
+                }
+                StylesMigration::<T>::put(cursor);
+            }
+            let changes = migrate_scheduled_changes::<T>();
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            // Old value, chunks, cursor, scheduled changes and version
+            T::DbWeight::get()
+                .reads_writes(changes + 2, changes + chunks + 3)
+                .saturating_add(Weight::from_parts(0, size))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            Ok((ScheduledChanges::<T>::iter_keys().count() as u32).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let changes = u32::decode(&mut &state[..])
+                .map_err(|_| "the pre upgrade state can't be decoded")?;
+
+            ensure!(
+                ScheduledChanges::<T>::iter().count() as u32 == changes,
+                "some scheduled changes have been lost during the migration"
+            );
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 1,
+                "the storage version has not been updated"
+            );
+
+            Ok(())
+        }
+    }
+
+    /// Worst case weight of migrating a parent style: two chunks read, a full parent style
+    /// written along with its sub styles index and a consumed chunk removed
+    pub fn parent_style_weight<T: Config>() -> Weight {
+        T::DbWeight::get()
+            .reads_writes(2, <MaxSubStyles as Get<u32>>::get() as u64 + 3)
+            .saturating_add(Weight::from_parts(
+                0,
+                2 * OldStylesChunk::max_encoded_len() as u64,
+            ))
+    }
+
+    /// Migrate the next parent styles of the old styles tree within the given weight, the chunks
+    /// are removed once the last one is migrated. The migration is stopped, keeping the pallet
+    /// blocked, if a parent style can't be decoded. Returns the consumed weight
+    pub fn migrate_step<T: Config>(mut cursor: MigrationCursor, limit: Weight) -> Weight {
+        let mut weight = T::DbWeight::get().reads_writes(1, 1);
+        if cursor.failed || !limit.all_gte(weight) {
+            return Weight::zero();
+        }
+
+        let length = ChunkLength::get();
+        for _ in 0..cursor.step.min(cursor.remaining) {
+            let step = parent_style_weight::<T>();
+            if !limit.all_gte(weight.saturating_add(step)) {
+                break;
+            }
+            weight.saturating_accrue(step);
+
+            let index = cursor.offset / length;
+            let mut bytes = OldStylesChunks::<T>::get(index)
+                .unwrap_or_default()
+                .into_inner();
+            bytes.extend(
+                OldStylesChunks::<T>::get(index + 1)
+                    .unwrap_or_default()
+                    .into_inner(),
+            );
+            let mut input = bytes
+                .get((cursor.offset % length) as usize..)
+                .unwrap_or_default();
+            let available = input.len();
+            let Ok((parent, subs)) = <(MusicStyleName, MusicSubStyles)>::decode(&mut input) else {
+                cursor.failed = true;
+                Pallet::<T>::deposit_event(Event::StylesMigrationFailed {
+                    remaining: cursor.remaining,
+                });
+                StylesMigration::<T>::put(cursor);
+                return weight;
+            };
+
+            for sub in subs.iter() {
+                SubStyleIndex::<T>::insert(sub, &parent, ());
+            }
+            ParentStyles::<T>::insert(&parent, subs);
+            cursor.offset += (available - input.len()) as u32;
+            cursor.remaining -= 1;
+            if cursor.offset / length > index {
+                OldStylesChunks::<T>::remove(index);
+            }
+        }
+
+        if cursor.remaining == 0 {
+            // At most the last chunk is left
+            weight.saturating_accrue(T::DbWeight::get().writes(2));
+            let _ = OldStylesChunks::<T>::clear(u32::MAX, None);
+            StylesMigration::<T>::kill();
+            Pallet::<T>::deposit_event(Event::StylesMigrated);
+        } else {
+            StylesMigration::<T>::put(cursor);
+        }
+
+        weight
+    }
+}
+
+pub mod v2 {
+    use super::*;
+
+    /// A scheduled change before the version 2, without the account which scheduled it
+    #[derive(Encode, Decode)]
+    pub struct OldScheduledChange<BlockNumber> {
+        pub change: StyleChange,
+        pub when: BlockNumber,
+    }
+
+    /// Record the account which scheduled the pending changes, they are all migrated without
+    /// account since it wasn't stored before
+    pub struct MigrateToV2<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() != 1 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut count = 0u64;
+            v3::ScheduledChanges::<T>::translate::<OldScheduledChange<BlockNumberFor<T>>, _>(
+                |_, old| {
+                    count += 1;
+                    Some(v3::OldScheduledChange {
+                        change: old.change,
+                        when: old.when,
+                        who: None,
+                    })
+                },
+            );
+            StorageVersion::new(2).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(count + 1, count + 1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            Ok((ScheduledChanges::<T>::iter_keys().count() as u32).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let count = u32::decode(&mut &state[..])
+                .map_err(|_| "the pre upgrade state can't be decoded")?;
+
+            ensure!(
+                v3::ScheduledChanges::<T>::iter().count() as u32 == count,
+                "some scheduled changes have been lost during the migration"
+            );
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() == 2,
+                "the storage version has not been updated"
+            );
+
+            Ok(())
+        }
+    }
+}
+
+pub mod v3 {
+    use super::*;
+
+    /// A scheduled change before the version 3, without the reason of the change
+    #[derive(Encode, Decode)]
+    pub struct OldScheduledChange<AccountId, BlockNumber> {
+        pub change: StyleChange,
+        pub when: BlockNumber,
+        pub who: Option<AccountId>,
+    }
+
+    /// Style changes waiting for their enactment, as stored by the version 2
+    #[frame_support::storage_alias]
+    pub type ScheduledChanges<T: Config> = StorageMap<
+        Pallet<T>,
+        Twox64Concat,
+        ChangeId,
+        OldScheduledChange<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Add a reason to the pending changes, they are all migrated without reason since it
+    /// wasn't stored before
+    pub struct MigrateToV3<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() != 2 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut count = 0u64;
+            ScheduledChanges::<T>::translate::<
+                OldScheduledChange<T::AccountId, BlockNumberFor<T>>,
+                _,
+            >(|_, old| {
+                count += 1;
+                Some(ScheduledChange {
+                    change: old.change,
+                    when: old.when,
+                    who: old.who,
+                    reason: None,
+                })
+            });
+            StorageVersion::new(3).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(count + 1, count + 1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            Ok((ScheduledChanges::<T>::iter_keys().count() as u32).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let count = u32::decode(&mut &state[..])
+                .map_err(|_| "the pre upgrade state can't be decoded")?;
+
+            ensure!(
+                ScheduledChanges::<T>::iter().count() as u32 == count,
+                "some scheduled changes have been lost during the migration"
+            );
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() == 3,
+                "the storage version has not been updated"
+            );
+
+            Ok(())
+        }
+    }
+}
\ No newline at end of file
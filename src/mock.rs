@@ -1,16 +1,14 @@
 use crate::{
-    self as pallet_music_styles,
+    self as pallet_music_styles, traits::StyleChangeHandler, GenesisStyle, StyleRef, WeightInfo,
 };
 use frame_support::{
-    construct_runtime,
-    traits::{ConstU64},
+    construct_runtime, ord_parameter_types, parameter_types,
+    traits::{ConstU32, ConstU64, EitherOfDiverse, Hooks},
+    weights::Weight,
 };
-use frame_system::EnsureRoot;
+use frame_system::{EnsureRoot, EnsureSignedBy};
 use sp_core::H256;
-use sp_runtime::{
-    BuildStorage,
-    traits::{ IdentityLookup},
-};
+use sp_runtime::{traits::IdentityLookup, BuildStorage};
 
 pub type AccountId = u64;
 pub type Block = frame_system::mocking::MockBlock<Test>;
@@ -18,6 +16,144 @@ pub type Block = frame_system::mocking::MockBlock<Test>;
 // Test accounts used
 // pub const ALICE: AccountId = 0; // Root
 pub const BOB: AccountId = 1; // Regular user
+pub const CHARLIE: AccountId = 2; // Pause guardian
+pub const DAVE: AccountId = 3; // Signed admin
+
+ord_parameter_types! {
+    pub const PauseGuardian: AccountId = CHARLIE;
+    pub const Admin: AccountId = DAVE;
+}
+
+parameter_types! {
+    pub static MaxNewParentStylesPerPeriod: u32 = 1_000;
+    pub static MaxNewSubStylesPerPeriod: u32 = 1_000;
+    pub static Notifications: Vec<Notification> = vec![];
+    pub static MaxHistory: u32 = 100;
+    pub static MaxChangeLogEntries: u32 = 100;
+}
+
+/// A change notified to the dependent pallets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Notification {
+    Added(StyleRef),
+    Renamed(StyleRef, StyleRef),
+    Removed(StyleRef),
+    Moved(StyleRef, StyleRef),
+}
+
+/// Record the notified changes so the tests can check them
+pub struct RecordStyleChanges;
+
+impl RecordStyleChanges {
+    fn record(notification: Notification) {
+        let mut notifications = Notifications::get();
+        notifications.push(notification);
+        Notifications::set(notifications);
+    }
+}
+
+impl StyleChangeHandler for RecordStyleChanges {
+    fn on_added(style: &StyleRef) {
+        Self::record(Notification::Added(style.clone()))
+    }
+    fn on_renamed(old: &StyleRef, new: &StyleRef) {
+        Self::record(Notification::Renamed(old.clone(), new.clone()))
+    }
+    fn on_removed(style: &StyleRef) {
+        Self::record(Notification::Removed(style.clone()))
+    }
+    fn on_moved(old: &StyleRef, new: &StyleRef) {
+        Self::record(Notification::Moved(old.clone(), new.clone()))
+    }
+    fn notification_weight() -> Weight {
+        Weight::from_parts(1_000, 0)
+    }
+}
+
+/// Benchmarked weights of the pallet, staging a chunk of an import weighs 1_000 per style so the
+/// tests can spread an import over several blocks
+pub struct MockWeights;
+
+impl WeightInfo for MockWeights {
+    fn add_style(n: u32, x: u32) -> Weight {
+        <() as WeightInfo>::add_style(n, x)
+    }
+    fn add_sub_style(n: u32, x: u32) -> Weight {
+        <() as WeightInfo>::add_sub_style(n, x)
+    }
+    fn update_style_name() -> Weight {
+        <() as WeightInfo>::update_style_name()
+    }
+    fn remove_style() -> Weight {
+        <() as WeightInfo>::remove_style()
+    }
+    fn cancel_change() -> Weight {
+        <() as WeightInfo>::cancel_change()
+    }
+    fn enact_changes(n: u32) -> Weight {
+        <() as WeightInfo>::enact_changes(n)
+    }
+    fn defer_changes(n: u32) -> Weight {
+        <() as WeightInfo>::defer_changes(n)
+    }
+    fn refresh_taxonomy_hash(p: u32) -> Weight {
+        <() as WeightInfo>::refresh_taxonomy_hash(p)
+    }
+    fn set_frozen() -> Weight {
+        <() as WeightInfo>::set_frozen()
+    }
+    fn set_style_lock() -> Weight {
+        <() as WeightInfo>::set_style_lock()
+    }
+    fn pause() -> Weight {
+        <() as WeightInfo>::pause()
+    }
+    fn unpause() -> Weight {
+        <() as WeightInfo>::unpause()
+    }
+    fn set_style_status() -> Weight {
+        <() as WeightInfo>::set_style_status()
+    }
+    fn revert_changes(n: u32) -> Weight {
+        <() as WeightInfo>::revert_changes(n)
+    }
+    fn set_taxonomy(n: u32, p: u32) -> Weight {
+        <() as WeightInfo>::set_taxonomy(n, p)
+    }
+    fn queue_import(c: u32, n: u32) -> Weight {
+        <() as WeightInfo>::queue_import(c, n)
+    }
+    fn stage_import(n: u32) -> Weight {
+        Weight::from_parts(1_000 * n as u64, 0)
+    }
+    fn cancel_import(c: u32, p: u32) -> Weight {
+        <() as WeightInfo>::cancel_import(c, p)
+    }
+    fn swap_import(n: u32, p: u32) -> Weight {
+        <() as WeightInfo>::swap_import(n, p)
+    }
+    fn apply_import_diff() -> Weight {
+        <() as WeightInfo>::apply_import_diff()
+    }
+    fn resume_styles_migration() -> Weight {
+        <() as WeightInfo>::resume_styles_migration()
+    }
+    fn parent_styles(p: u32) -> Weight {
+        <() as WeightInfo>::parent_styles(p)
+    }
+    fn sub_styles(p: u32) -> Weight {
+        <() as WeightInfo>::sub_styles(p)
+    }
+    fn is_parent_style() -> Weight {
+        <() as WeightInfo>::is_parent_style()
+    }
+    fn is_sub_style(p: u32) -> Weight {
+        <() as WeightInfo>::is_sub_style(p)
+    }
+    fn exist(p: u32) -> Weight {
+        <() as WeightInfo>::exist(p)
+    }
+}
 
 // Configure a mock runtime to test the pallet.
 construct_runtime!(
@@ -56,23 +192,32 @@ impl frame_system::Config for Test {
 
 impl pallet_music_styles::Config for Test {
     type RuntimeEvent = RuntimeEvent;
-    type AdminOrigin = EnsureRoot<AccountId>;
-    type Weights = ();
+    type AdminOrigin = EitherOfDiverse<EnsureRoot<AccountId>, EnsureSignedBy<Admin, AccountId>>;
+    type PauseOrigin = EnsureSignedBy<PauseGuardian, AccountId>;
+    type TaxonomyOrigin = EitherOfDiverse<EnsureRoot<AccountId>, EnsureSignedBy<Admin, AccountId>>;
+    type OnStyleChange = RecordStyleChanges;
+    type EnactmentDelay = ConstU64<10>;
+    type MaxScheduledPerBlock = ConstU32<5>;
+    type RatePeriod = ConstU64<20>;
+    type MaxNewParentStylesPerPeriod = MaxNewParentStylesPerPeriod;
+    type MaxNewSubStylesPerPeriod = MaxNewSubStylesPerPeriod;
+    type MaxHistory = MaxHistory;
+    type MaxChangeLogEntries = MaxChangeLogEntries;
+    type Weights = MockWeights;
 }
 
 // Build genesis storage according to the mock runtime.
 pub(crate) fn new_test_ext(include_genesis: bool) -> sp_io::TestExternalities {
-    let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+    let mut storage = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
 
     let pallet_config: pallet_music_styles::GenesisConfig<Test> = match include_genesis {
         true => pallet_music_styles::GenesisConfig {
             styles: vec![
-                ("Raggae".into(), vec![]),
-                (
-                    "Rap".into(),
-                    vec!["Drill".into(), "Trap".into(), "Hardcore".into()],
-                ),
-                ("Rock".into(), vec!["Hardcore".into()]),
+                GenesisStyle::new("Raggae", vec![]),
+                GenesisStyle::new("Rap", vec!["Drill", "Trap", "Hardcore"]),
+                GenesisStyle::new("Rock", vec!["Hardcore"]),
             ],
             phantom: Default::default(),
         },
@@ -86,3 +231,13 @@ pub(crate) fn new_test_ext(include_genesis: bool) -> sp_io::TestExternalities {
     ext.execute_with(|| System::set_block_number(1));
     ext
 }
+
+/// Run the pallet hooks until the given block number, the blocks are left empty
+pub(crate) fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        MusicStylesPallet::on_initialize(next);
+        MusicStylesPallet::on_idle(next, Weight::MAX);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,98 @@
+//! Runtime API to query the music styles tree, so clients don't depend on the storage layout
+//!
+//! Styles names are given as raw bytes. The style queries (`all_styles`, `children`, `resolve`,
+//! `exists` and `path`) hide the draft styles, while the taxonomy queries (`taxonomy_hash`,
+//! `styles_at_version` and `taxonomy_diff`) cover the whole tree, draft styles included.
+
+use super::*;
+
+sp_api::decl_runtime_apis! {
+    #[api_version(4)]
+    pub trait MusicStylesApi {
+        /// All the music styles, without the draft styles
+        fn all_styles() -> MusicStyleDB;
+
+        /// Visible sub styles of a parent style, `None` if the parent style doesn't exist or is a
+        /// draft
+        fn children(parent: Vec<u8>) -> Option<Vec<MusicStyleName>>;
+
+        /// Find a visible style from its name or one of its aliases, parent styles take precedence
+        /// over sub styles
+        fn resolve(name: Vec<u8>) -> Option<StyleRef>;
+
+        /// Whether a visible parent style or sub style resolves from the given name
+        fn exists(name: Vec<u8>) -> bool;
+
+        /// Path from the first level of the tree to the style resolved from the given name
+        fn path(name: Vec<u8>) -> Option<Vec<MusicStyleName>>;
+
+        /// Version of the styles tree, bumped on every addition, rename, move or removal of a style
+        #[api_version(2)]
+        fn taxonomy_version() -> u64;
+
+        /// Blake2-256 hash of the SCALE encoded styles tree, draft styles included
+        #[api_version(2)]
+        fn taxonomy_hash() -> H256;
+
+        /// The styles tree, draft styles included, as it was at the given taxonomy version
+        /// `None` if the version is in the future or older than the kept history
+        #[api_version(3)]
+        fn styles_at_version(version: u64) -> Option<MusicStyleDB>;
+
+        /// Changes turning the styles tree at the `from` version into the one at the `to` version,
+        /// draft styles included. `None` if one of the versions is in the future or older than the
+        /// kept history
+        #[api_version(4)]
+        fn taxonomy_diff(from: u64, to: u64) -> Option<Vec<StyleChange>>;
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn children(parent: Vec<u8>) -> Option<Vec<MusicStyleName>> {
+        let parent = MusicStyleName::try_from(parent).ok()?;
+        if !Self::has_parent_style(&parent) {
+            return None;
+        }
+
+        <ParentStyles<T>>::get(&parent).map(|subs| {
+            subs.into_iter()
+                .filter(|sub| Self::status_of(sub).is_visible())
+                .collect()
+        })
+    }
+
+    pub fn resolve(name: Vec<u8>) -> Option<StyleRef> {
+        let name = MusicStyleName::try_from(name).ok()?;
+
+        Self::resolve_name(&name).or_else(|| Self::resolve_name(&<StyleAliases<T>>::get(&name)?))
+    }
+
+    fn resolve_name(name: &MusicStyleName) -> Option<StyleRef> {
+        if Self::has_parent_style(name) {
+            return Some(StyleRef::Parent(name.clone()));
+        }
+
+        Self::parents_of(name)
+            .into_iter()
+            .next()
+            .map(|parent| StyleRef::Sub(parent, name.clone()))
+    }
+
+    pub fn taxonomy_diff(from: u64, to: u64) -> Option<Vec<StyleChange>> {
+        Some(diff(
+            &Self::styles_at_version(from)?,
+            &Self::styles_at_version(to)?,
+        ))
+    }
+
+    pub fn exists(name: Vec<u8>) -> bool {
+        Self::resolve(name).is_some()
+    }
+
+    pub fn path(name: Vec<u8>) -> Option<Vec<MusicStyleName>> {
+        Self::resolve(name).map(|style| match style {
+            StyleRef::Parent(name) => vec![name],
+            StyleRef::Sub(parent, name) => vec![parent, name],
+        })
+    }
+}
\ No newline at end of file
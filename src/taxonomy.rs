@@ -0,0 +1,353 @@
+//! Curated genre tree bundled with the `default-taxonomy` feature, so test networks don't have to
+//! write their own genesis styles. Use it through `GenesisConfig::with_default_taxonomy`.
+
+/// Version of the bundled taxonomy, bumped on every change of the dataset
+pub const DEFAULT_TAXONOMY_VERSION: u32 = 1;
+
+/// Parent styles of the bundled taxonomy along with their sub styles
+pub const DEFAULT_TAXONOMY: &[(&str, &[&str])] = &[
+    (
+        "Rock",
+        &[
+            "Alternative Rock",
+            "Classic Rock",
+            "Garage Rock",
+            "Grunge",
+            "Hard Rock",
+            "Indie Rock",
+            "Post-Rock",
+            "Progressive Rock",
+            "Psychedelic Rock",
+            "Punk Rock",
+            "Rockabilly",
+            "Shoegaze",
+            "Stoner Rock",
+            "Surf Rock",
+        ],
+    ),
+    (
+        "Metal",
+        &[
+            "Black Metal",
+            "Death Metal",
+            "Doom Metal",
+            "Folk Metal",
+            "Glam Metal",
+            "Heavy Metal",
+            "Metalcore",
+            "Nu Metal",
+            "Power Metal",
+            "Progressive Metal",
+            "Sludge Metal",
+            "Speed Metal",
+            "Symphonic Metal",
+            "Thrash Metal",
+        ],
+    ),
+    (
+        "Punk",
+        &[
+            "Crust Punk",
+            "Emo",
+            "Hardcore",
+            "Oi!",
+            "Pop Punk",
+            "Post-Punk",
+            "Skate Punk",
+            "Ska Punk",
+        ],
+    ),
+    (
+        "Pop",
+        &[
+            "Art Pop",
+            "Baroque Pop",
+            "Bubblegum Pop",
+            "Dance Pop",
+            "Dream Pop",
+            "Electropop",
+            "Indie Pop",
+            "J-Pop",
+            "K-Pop",
+            "Power Pop",
+            "Synth-Pop",
+            "Teen Pop",
+        ],
+    ),
+    (
+        "Hip-Hop",
+        &[
+            "Boom Bap",
+            "Cloud Rap",
+            "Conscious Hip-Hop",
+            "Drill",
+            "Gangsta Rap",
+            "Grime",
+            "Horrorcore",
+            "Jazz Rap",
+            "Lo-Fi Hip-Hop",
+            "Mumble Rap",
+            "Old School Hip-Hop",
+            "Trap",
+        ],
+    ),
+    (
+        "R&B",
+        &[
+            "Alternative R&B",
+            "Classic R&B",
+            "Contemporary R&B",
+            "Doo-Wop",
+            "New Jack Swing",
+            "Quiet Storm",
+        ],
+    ),
+    (
+        "Soul",
+        &[
+            "Blue-Eyed Soul",
+            "Neo Soul",
+            "Northern Soul",
+            "Philly Soul",
+            "Psychedelic Soul",
+            "Southern Soul",
+        ],
+    ),
+    (
+        "Funk",
+        &[
+            "Boogie",
+            "Electro-Funk",
+            "Funk Rock",
+            "G-Funk",
+            "Go-Go",
+            "Jazz Funk",
+            "P-Funk",
+        ],
+    ),
+    (
+        "Electronic",
+        &[
+            "Ambient",
+            "Breakbeat",
+            "Chillout",
+            "Downtempo",
+            "Drum and Bass",
+            "Dubstep",
+            "Electro",
+            "Hardstyle",
+            "IDM",
+            "Jungle",
+            "Synthwave",
+            "Trip Hop",
+            "UK Garage",
+        ],
+    ),
+    (
+        "House",
+        &[
+            "Acid House",
+            "Afro House",
+            "Chicago House",
+            "Deep House",
+            "Electro House",
+            "French House",
+            "Future House",
+            "Progressive House",
+            "Tech House",
+            "Tropical House",
+        ],
+    ),
+    (
+        "Techno",
+        &[
+            "Acid Techno",
+            "Detroit Techno",
+            "Dub Techno",
+            "Hard Techno",
+            "Industrial Techno",
+            "Melodic Techno",
+            "Minimal Techno",
+        ],
+    ),
+    (
+        "Trance",
+        &[
+            "Goa Trance",
+            "Hard Trance",
+            "Progressive Trance",
+            "Psytrance",
+            "Tech Trance",
+            "Uplifting Trance",
+            "Vocal Trance",
+        ],
+    ),
+    (
+        "Jazz",
+        &[
+            "Acid Jazz",
+            "Bebop",
+            "Big Band",
+            "Cool Jazz",
+            "Dixieland",
+            "Free Jazz",
+            "Fusion",
+            "Gypsy Jazz",
+            "Hard Bop",
+            "Latin Jazz",
+            "Modal Jazz",
+            "Smooth Jazz",
+            "Swing",
+        ],
+    ),
+    (
+        "Blues",
+        &[
+            "Blues Rock",
+            "Chicago Blues",
+            "Delta Blues",
+            "Electric Blues",
+            "Jump Blues",
+            "Piedmont Blues",
+            "Soul Blues",
+            "Texas Blues",
+        ],
+    ),
+    (
+        "Country",
+        &[
+            "Alt-Country",
+            "Americana",
+            "Bluegrass",
+            "Bro-Country",
+            "Country Pop",
+            "Country Rock",
+            "Honky Tonk",
+            "Outlaw Country",
+            "Western Swing",
+        ],
+    ),
+    (
+        "Folk",
+        &[
+            "Celtic Folk",
+            "Chamber Folk",
+            "Contemporary Folk",
+            "Folk Rock",
+            "Freak Folk",
+            "Indie Folk",
+            "Neofolk",
+            "Traditional Folk",
+        ],
+    ),
+    (
+        "Classical",
+        &[
+            "Baroque",
+            "Chamber Music",
+            "Choral",
+            "Contemporary Classical",
+            "Early Music",
+            "Impressionist",
+            "Minimalism",
+            "Opera",
+            "Renaissance",
+            "Romantic",
+            "Symphonic",
+        ],
+    ),
+    (
+        "Reggae",
+        &[
+            "Dancehall",
+            "Dub",
+            "Lovers Rock",
+            "Ragga",
+            "Reggae Fusion",
+            "Rocksteady",
+            "Roots Reggae",
+            "Ska",
+        ],
+    ),
+    (
+        "Latin",
+        &[
+            "Bachata",
+            "Banda",
+            "Bolero",
+            "Bossa Nova",
+            "Cumbia",
+            "Latin Pop",
+            "Mambo",
+            "Merengue",
+            "Reggaeton",
+            "Salsa",
+            "Samba",
+            "Tango",
+        ],
+    ),
+    (
+        "African",
+        &[
+            "Afrobeat",
+            "Afrobeats",
+            "Amapiano",
+            "Gqom",
+            "Highlife",
+            "Juju",
+            "Kwaito",
+            "Mbalax",
+            "Rai",
+            "Soukous",
+        ],
+    ),
+    (
+        "Asian",
+        &[
+            "Bhangra",
+            "Cantopop",
+            "Carnatic",
+            "Enka",
+            "Filmi",
+            "Gamelan",
+            "Hindustani Classical",
+            "Mandopop",
+            "Qawwali",
+        ],
+    ),
+    (
+        "Gospel",
+        &[
+            "Christian Rock",
+            "Contemporary Christian",
+            "Southern Gospel",
+            "Spirituals",
+            "Urban Contemporary Gospel",
+            "Worship",
+        ],
+    ),
+    (
+        "Experimental",
+        &[
+            "Avant-Garde",
+            "Drone",
+            "Free Improvisation",
+            "Glitch",
+            "Industrial",
+            "Musique Concrete",
+            "Noise",
+            "Sound Art",
+        ],
+    ),
+    (
+        "Soundtrack",
+        &[
+            "Film Score",
+            "Library Music",
+            "Musical Theatre",
+            "Television Music",
+            "Trailer Music",
+            "Video Game Music",
+        ],
+    ),
+];
\ No newline at end of file
@@ -27,6 +27,35 @@ fn generate_random_name(i: u32) -> Vec<u8> {
 fn assert_last_event(event: super::Event<Test>) {
     System::assert_last_event(mock::RuntimeEvent::MusicStylesPallet(event))
 }
+
+/// Panic if the given event has not been emitted
+fn assert_has_event(event: super::Event<Test>) {
+    System::assert_has_event(mock::RuntimeEvent::MusicStylesPallet(event))
+}
+
+fn bounded(name: &[u8]) -> MusicStyleName {
+    name.to_vec().try_into().unwrap()
+}
+
+/// Styles tree holding the given parent styles and their sub styles
+fn tree(styles: &[(&str, &[&str])]) -> MusicStyleDB {
+    let mut tree = MusicStyleDB::new();
+    for (parent, subs) in styles {
+        let subs = subs.iter().map(|sub| bounded(sub.as_bytes())).collect();
+        tree.try_insert(bounded(parent.as_bytes()), BoundedVec::truncate_from(subs))
+            .unwrap();
+    }
+    tree
+}
+
+fn parent(name: &str) -> StyleRef {
+    StyleRef::Parent(bounded(name.as_bytes()))
+}
+
+fn sub(parent: &str, name: &str) -> StyleRef {
+    StyleRef::Sub(bounded(parent.as_bytes()), bounded(name.as_bytes()))
+}
+
 #[test]
 fn test_genesis() {
     new_test_ext(true).execute_with(|| {
@@ -72,6 +101,7 @@ mod add {
                 MusicStylesPallet::add_style(
                     RuntimeOrigin::signed(BOB),
                     b"Reggae".to_vec().into(),
+                    None,
                     None
                 ),
                 BadOrigin
@@ -89,7 +119,7 @@ mod add {
 
             // Too long main style name
             assert_noop!(
-                MusicStylesPallet::add_style(RuntimeOrigin::root(), long_name.clone(), None),
+                MusicStylesPallet::add_style(RuntimeOrigin::root(), long_name.clone(), None, None),
                 Error::<Test>::NameTooLong
             );
 
@@ -98,7 +128,8 @@ mod add {
                 MusicStylesPallet::add_style(
                     RuntimeOrigin::root(),
                     b"test".to_vec(),
-                    Some(vec![long_name])
+                    Some(vec![long_name]),
+                    None
                 ),
                 Error::<Test>::NameTooLong
             );
@@ -113,6 +144,7 @@ mod add {
                 assert_ok!(MusicStylesPallet::add_style(
                     RuntimeOrigin::root(),
                     generate_random_name(i),
+                    None,
                     None
                 ));
             }
@@ -122,6 +154,7 @@ mod add {
                 MusicStylesPallet::add_style(
                     RuntimeOrigin::root(),
                     generate_random_name(<MaxParentStyles as Get<u32>>::get()),
+                    None,
                     None
                 ),
                 Error::<Test>::StylesCapacity
@@ -139,7 +172,12 @@ mod add {
             }
 
             assert_noop!(
-                MusicStylesPallet::add_style(RuntimeOrigin::root(), b"Test".to_vec(), Some(sub)),
+                MusicStylesPallet::add_style(
+                    RuntimeOrigin::root(),
+                    b"Test".to_vec(),
+                    Some(sub),
+                    None
+                ),
                 Error::<Test>::StylesCapacity
             );
         });
@@ -157,7 +195,8 @@ mod add {
             assert_ok!(MusicStylesPallet::add_style(
                 RuntimeOrigin::root(),
                 name.clone(),
-                subs.clone()
+                subs.clone(),
+                None
             ));
 
             // Check that the storage have been updated
@@ -173,12 +212,21 @@ mod add {
             // Check that the events has been called
             assert_eq!(
                 System::events()[0].event,
-                mock::RuntimeEvent::MusicStylesPallet(StyleAdded(name.clone()))
+                mock::RuntimeEvent::MusicStylesPallet(StyleAdded {
+                    name: bounded(&name),
+                    who: None,
+                    version: 1
+                })
             );
             for (i, sub) in subs.unwrap().iter().enumerate() {
                 assert_eq!(
                     System::events()[i + 1].event,
-                    mock::RuntimeEvent::MusicStylesPallet(SubStyleAdded(sub.clone()))
+                    mock::RuntimeEvent::MusicStylesPallet(SubStyleAdded {
+                        parent: bounded(&name),
+                        name: bounded(sub),
+                        who: None,
+                        version: i as u64 + 2
+                    })
                 );
             }
         });
@@ -195,7 +243,8 @@ mod add_sub_style {
                 MusicStylesPallet::add_sub_style(
                     RuntimeOrigin::signed(BOB),
                     b"Rap".to_vec(),
-                    vec![b"New".to_vec()]
+                    vec![b"New".to_vec()],
+                    None
                 ),
                 BadOrigin
             );
@@ -209,7 +258,8 @@ mod add_sub_style {
                 MusicStylesPallet::add_sub_style(
                     RuntimeOrigin::root(),
                     b"Rap".to_vec(),
-                    vec![b"Drill".to_vec()]
+                    vec![b"Drill".to_vec()],
+                    None
                 ),
                 Error::<Test>::NameAlreadyExists
             );
@@ -224,6 +274,7 @@ mod add_sub_style {
                     RuntimeOrigin::root(),
                     b"Inexisting Style".to_vec(),
                     vec![b"test sub style".to_vec()],
+                    None
                 ),
                 Error::<Test>::StyleNotFound
             );
@@ -243,7 +294,8 @@ mod add_sub_style {
                 MusicStylesPallet::add_sub_style(
                     RuntimeOrigin::root(),
                     b"Rap".to_vec(),
-                    vec![long_name]
+                    vec![long_name],
+                    None
                 ),
                 Error::<Test>::NameTooLong
             );
@@ -259,7 +311,8 @@ mod add_sub_style {
                 assert_ok!(MusicStylesPallet::add_sub_style(
                     RuntimeOrigin::root(),
                     b"Raggae".to_vec(),
-                    vec![generate_random_name(i)]
+                    vec![generate_random_name(i)],
+                    None
                 ));
             }
 
@@ -268,7 +321,8 @@ mod add_sub_style {
                 MusicStylesPallet::add_sub_style(
                     RuntimeOrigin::root(),
                     b"Raggae".to_vec(),
-                    vec![b"Too much".to_vec()]
+                    vec![b"Too much".to_vec()],
+                    None
                 ),
                 Error::<Test>::StylesCapacity
             );
@@ -285,7 +339,8 @@ mod add_sub_style {
             assert_ok!(MusicStylesPallet::add_sub_style(
                 RuntimeOrigin::root(),
                 b"Rap".to_vec(),
-                vec![new_name.clone()]
+                vec![new_name.clone()],
+                None
             ));
 
             let after_styles: MusicStyleDB = MusicStylesPallet::get_styles();
@@ -301,7 +356,3917 @@ mod add_sub_style {
                 .unwrap();
 
             // Check that the event has been called
-            assert_last_event(SubStyleAdded(new_name));
+            assert_last_event(SubStyleAdded {
+                parent: bounded(b"Rap"),
+                name: bounded(&new_name),
+                who: None,
+                version: 1,
+            });
+        });
+    }
+}
+
+mod mutate {
+    use super::*;
+    use allfeat_support::traits::music::style::MutateMusicStyles;
+
+    #[test]
+    fn other_pallets_can_add_styles() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(<MusicStylesPallet as MutateMusicStyles>::add_parent_style(
+                bounded(b"Jazz")
+            ));
+            assert_last_event(StyleAdded {
+                name: bounded(b"Jazz"),
+                who: None,
+                version: 1,
+            });
+
+            assert_ok!(<MusicStylesPallet as MutateMusicStyles>::add_sub_style(
+                bounded(b"Bebop"),
+                bounded(b"Jazz")
+            ));
+            assert_last_event(SubStyleAdded {
+                parent: bounded(b"Jazz"),
+                name: bounded(b"Bebop"),
+                who: None,
+                version: 2,
+            });
+
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Bebop".to_vec()),
+                Some(StyleRef::Sub(bounded(b"Jazz"), bounded(b"Bebop")))
+            );
+            assert_eq!(
+                Notifications::get(),
+                vec![
+                    Notification::Added(StyleRef::Parent(bounded(b"Jazz"))),
+                    Notification::Added(StyleRef::Sub(bounded(b"Jazz"), bounded(b"Bebop"))),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn existing_names_are_rejected() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                <MusicStylesPallet as MutateMusicStyles>::add_parent_style(bounded(b"Rap")),
+                Error::<Test>::NameAlreadyExists
+            );
+            assert_noop!(
+                <MusicStylesPallet as MutateMusicStyles>::add_sub_style(
+                    bounded(b"Drill"),
+                    bounded(b"Rap")
+                ),
+                Error::<Test>::NameAlreadyExists
+            );
+            assert_noop!(
+                <MusicStylesPallet as MutateMusicStyles>::add_sub_style(
+                    bounded(b"Drill"),
+                    bounded(b"Jazz")
+                ),
+                Error::<Test>::StyleNotFound
+            );
+        });
+    }
+
+    #[test]
+    fn extrinsics_checks_apply() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+            assert_noop!(
+                <MusicStylesPallet as MutateMusicStyles>::add_parent_style(bounded(b"Jazz")),
+                Error::<Test>::StyleFrozen
+            );
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), false));
+
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+            assert_noop!(
+                <MusicStylesPallet as MutateMusicStyles>::add_sub_style(
+                    bounded(b"Dub"),
+                    bounded(b"Raggae")
+                ),
+                Error::<Test>::PalletPaused
+            );
+            assert_ok!(MusicStylesPallet::unpause(RuntimeOrigin::signed(CHARLIE)));
+
+            MaxNewSubStylesPerPeriod::set(0);
+            assert_noop!(
+                <MusicStylesPallet as MutateMusicStyles>::add_sub_style(
+                    bounded(b"Dub"),
+                    bounded(b"Raggae")
+                ),
+                Error::<Test>::RateLimited
+            );
+        });
+    }
+}
+
+mod scheduled_changes {
+    use super::*;
+
+    const DELAY: u64 = 10;
+
+    #[test]
+    fn non_admin_cannot_schedule_changes() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::update_style_name(
+                    RuntimeOrigin::signed(BOB),
+                    b"Rap".to_vec(),
+                    None,
+                    b"Hip-Hop".to_vec(),
+                    None
+                ),
+                BadOrigin
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(
+                    RuntimeOrigin::signed(BOB),
+                    b"Rap".to_vec(),
+                    None,
+                    None
+                ),
+                BadOrigin
+            );
+        });
+    }
+
+    #[test]
+    fn cannot_schedule_change_of_unexisting_style() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::remove_style(
+                    RuntimeOrigin::root(),
+                    b"Jazz".to_vec(),
+                    None,
+                    None
+                ),
+                Error::<Test>::StyleNotFound
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(
+                    RuntimeOrigin::root(),
+                    b"Rock".to_vec(),
+                    Some(b"Drill".to_vec()),
+                    None
+                ),
+                Error::<Test>::StyleNotFound
+            );
+        });
+    }
+
+    #[test]
+    fn cannot_rename_to_existing_name() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::update_style_name(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    None,
+                    b"Rock".to_vec(),
+                    None
+                ),
+                Error::<Test>::NameAlreadyExists
+            );
+            assert_noop!(
+                MusicStylesPallet::update_style_name(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    Some(b"Drill".to_vec()),
+                    b"Trap".to_vec(),
+                    None
+                ),
+                Error::<Test>::NameAlreadyExists
+            );
+        });
+    }
+
+    #[test]
+    fn rename_is_enacted_after_delay() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                b"Hip-Hop".to_vec(),
+                None
+            ));
+            assert_last_event(StyleChangeScheduled {
+                id: 0,
+                change: StyleChange::Rename {
+                    style: StyleRef::Parent(bounded(b"Rap")),
+                    new_name: bounded(b"Hip-Hop"),
+                },
+                when: 1 + DELAY,
+                who: None,
+            });
+
+            // Nothing changes before the enactment block
+            run_to_block(DELAY);
+            assert!(MusicStylesPallet::get_styles().contains_key(&bounded(b"Rap")));
+
+            run_to_block(1 + DELAY);
+            let styles = MusicStylesPallet::get_styles();
+            assert!(!styles.contains_key(&bounded(b"Rap")));
+            assert_eq!(styles.get(&bounded(b"Hip-Hop")).unwrap().len(), 3);
+            assert!(MusicStylesPallet::scheduled_change(0).is_none());
+
+            assert_has_event(StyleNameUpdated {
+                old_name: bounded(b"Rap"),
+                new_name: bounded(b"Hip-Hop"),
+                who: None,
+                version: 1,
+            });
+            assert_last_event(StyleChangeEnacted { id: 0 });
+        });
+    }
+
+    #[test]
+    fn sub_style_rename_is_enacted_after_delay() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Hardcore".to_vec()),
+                b"Horrorcore".to_vec(),
+                None
+            ));
+
+            run_to_block(1 + DELAY);
+            let styles = MusicStylesPallet::get_styles();
+            let rap_subs = styles.get(&bounded(b"Rap")).unwrap();
+            assert_eq!(rap_subs[2], bounded(b"Horrorcore"));
+
+            // The same sub style name under another parent is kept
+            assert!(styles
+                .get(&bounded(b"Rock"))
+                .unwrap()
+                .contains(&bounded(b"Hardcore")));
+
+            assert_has_event(SubStyleNameUpdated {
+                parent: bounded(b"Rap"),
+                old_name: bounded(b"Hardcore"),
+                new_name: bounded(b"Horrorcore"),
+                who: None,
+                version: 1,
+            });
+        });
+    }
+
+    #[test]
+    fn removals_are_enacted_after_delay() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                None
+            ));
+
+            run_to_block(1 + DELAY);
+            let styles = MusicStylesPallet::get_styles();
+            assert!(!styles.contains_key(&bounded(b"Rock")));
+            assert_eq!(
+                styles.get(&bounded(b"Rap")).unwrap().to_vec(),
+                vec![bounded(b"Drill"), bounded(b"Hardcore")]
+            );
+
+            assert_has_event(StyleRemoved {
+                name: bounded(b"Rock"),
+                who: None,
+                version: 1,
+            });
+            assert_has_event(SubStyleRemoved {
+                parent: bounded(b"Rap"),
+                name: bounded(b"Trap"),
+                who: None,
+                version: 2,
+            });
+        });
+    }
+
+    #[test]
+    fn cancelled_change_is_not_enacted() {
+        new_test_ext(true).execute_with(|| {
+            let before_styles: MusicStyleDB = MusicStylesPallet::get_styles();
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+
+            assert_noop!(
+                MusicStylesPallet::cancel_change(RuntimeOrigin::signed(BOB), 0),
+                BadOrigin
+            );
+            assert_noop!(
+                MusicStylesPallet::cancel_change(RuntimeOrigin::root(), 1),
+                Error::<Test>::ChangeNotFound
+            );
+
+            assert_ok!(MusicStylesPallet::cancel_change(RuntimeOrigin::root(), 0));
+            assert_last_event(StyleChangeCancelled { id: 0, who: None });
+
+            run_to_block(1 + DELAY);
+            assert_eq!(MusicStylesPallet::get_styles(), before_styles);
+        });
+    }
+
+    #[test]
+    fn outdated_change_fails_at_enactment() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Drill".to_vec()),
+                None
+            ));
+
+            run_to_block(1 + DELAY);
+            assert_has_event(StyleChangeEnacted { id: 0 });
+            assert_last_event(StyleChangeFailed {
+                id: 1,
+                error: Error::<Test>::StyleNotFound.into(),
+            });
+        });
+    }
+
+    #[test]
+    fn cannot_exceed_changes_per_block() {
+        new_test_ext(true).execute_with(|| {
+            for _ in 0..5 {
+                assert_ok!(MusicStylesPallet::remove_style(
+                    RuntimeOrigin::root(),
+                    b"Rock".to_vec(),
+                    None,
+                    None
+                ));
+            }
+
+            assert_noop!(
+                MusicStylesPallet::remove_style(
+                    RuntimeOrigin::root(),
+                    b"Rock".to_vec(),
+                    None,
+                    None
+                ),
+                Error::<Test>::TooManyScheduledChanges
+            );
         });
     }
 }
+
+mod freeze {
+    use super::*;
+
+    #[test]
+    fn non_admin_cannot_freeze() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::set_frozen(RuntimeOrigin::signed(BOB), true),
+                BadOrigin
+            );
+            assert_noop!(
+                MusicStylesPallet::set_style_lock(
+                    RuntimeOrigin::signed(BOB),
+                    b"Rap".to_vec(),
+                    None,
+                    true
+                ),
+                BadOrigin
+            );
+        });
+    }
+
+    #[test]
+    fn frozen_tree_rejects_all_changes() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+            assert_last_event(FrozenUpdated {
+                frozen: true,
+                who: None,
+            });
+
+            assert_noop!(
+                MusicStylesPallet::add_style(RuntimeOrigin::root(), b"Jazz".to_vec(), None, None),
+                Error::<Test>::StyleFrozen
+            );
+            assert_noop!(
+                MusicStylesPallet::add_sub_style(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    vec![b"Grime".to_vec()],
+                    None
+                ),
+                Error::<Test>::StyleFrozen
+            );
+            assert_noop!(
+                MusicStylesPallet::update_style_name(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    None,
+                    b"Hip-Hop".to_vec(),
+                    None
+                ),
+                Error::<Test>::StyleFrozen
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(RuntimeOrigin::root(), b"Rap".to_vec(), None, None),
+                Error::<Test>::StyleFrozen
+            );
+
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), false));
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                None,
+                None
+            ));
+        });
+    }
+
+    #[test]
+    fn scheduled_change_fails_if_frozen_at_enactment() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+
+            run_to_block(11);
+            assert!(MusicStylesPallet::get_styles().contains_key(&bounded(b"Rap")));
+            assert_last_event(StyleChangeFailed {
+                id: 0,
+                error: Error::<Test>::StyleFrozen.into(),
+            });
+        });
+    }
+
+    #[test]
+    fn locked_style_cannot_be_renamed_or_removed() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_lock(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                true
+            ));
+            assert_last_event(StyleLockUpdated {
+                style: StyleRef::Parent(bounded(b"Rap")),
+                locked: true,
+                who: None,
+            });
+
+            assert_noop!(
+                MusicStylesPallet::update_style_name(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    None,
+                    b"Hip-Hop".to_vec(),
+                    None
+                ),
+                Error::<Test>::StyleFrozen
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(RuntimeOrigin::root(), b"Rap".to_vec(), None, None),
+                Error::<Test>::StyleFrozen
+            );
+
+            // Sub styles can still grow beneath the locked style
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                vec![b"Grime".to_vec()],
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Drill".to_vec()),
+                None
+            ));
+
+            // Other styles are not affected
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+
+            assert_ok!(MusicStylesPallet::set_style_lock(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                false
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+        });
+    }
+
+    #[test]
+    fn locked_sub_style_locks_its_parent_structure() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_lock(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Drill".to_vec()),
+                true
+            ));
+
+            assert_noop!(
+                MusicStylesPallet::remove_style(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    Some(b"Drill".to_vec()),
+                    None
+                ),
+                Error::<Test>::StyleFrozen
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(RuntimeOrigin::root(), b"Rap".to_vec(), None, None),
+                Error::<Test>::StyleFrozen
+            );
+        });
+    }
+
+    #[test]
+    fn cannot_lock_unexisting_style() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::set_style_lock(
+                    RuntimeOrigin::root(),
+                    b"Rock".to_vec(),
+                    Some(b"Drill".to_vec()),
+                    true
+                ),
+                Error::<Test>::StyleNotFound
+            );
+        });
+    }
+}
+
+mod pause {
+    use super::*;
+    use frame_support::traits::Contains;
+
+    #[test]
+    fn only_pause_origin_can_pause() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::pause(RuntimeOrigin::signed(BOB)),
+                BadOrigin
+            );
+            assert_noop!(MusicStylesPallet::pause(RuntimeOrigin::root()), BadOrigin);
+
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+            assert_last_event(Paused { who: Some(CHARLIE) });
+            assert!(MusicStylesPallet::is_paused());
+
+            assert_noop!(
+                MusicStylesPallet::unpause(RuntimeOrigin::signed(BOB)),
+                BadOrigin
+            );
+            assert_ok!(MusicStylesPallet::unpause(RuntimeOrigin::signed(CHARLIE)));
+            assert_last_event(Unpaused { who: Some(CHARLIE) });
+            assert!(!MusicStylesPallet::is_paused());
+        });
+    }
+
+    #[test]
+    fn paused_pallet_rejects_mutating_calls() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+
+            assert_noop!(
+                MusicStylesPallet::add_style(RuntimeOrigin::root(), b"Jazz".to_vec(), None, None),
+                Error::<Test>::PalletPaused
+            );
+            assert_noop!(
+                MusicStylesPallet::add_sub_style(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    vec![b"Grime".to_vec()],
+                    None
+                ),
+                Error::<Test>::PalletPaused
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(RuntimeOrigin::root(), b"Rap".to_vec(), None, None),
+                Error::<Test>::PalletPaused
+            );
+            assert_noop!(
+                MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true),
+                Error::<Test>::PalletPaused
+            );
+
+            // Reads are still available
+            assert!(MusicStylesPallet::exist(&bounded(b"Rap")));
+            assert!(MusicStylesPallet::is_sub_style(&bounded(b"Drill")));
+        });
+    }
+
+    #[test]
+    fn scheduled_change_waits_while_paused() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+
+            run_to_block(11);
+            assert!(MusicStylesPallet::get_styles().contains_key(&bounded(b"Rap")));
+            assert_last_event(StyleChangeDeferred { id: 0 });
+
+            run_to_block(14);
+            assert_eq!(DeferredRange::<Test>::get(), (0, 1));
+            assert_eq!(DeferredChanges::<Test>::get(0), Some(0));
+
+            assert_ok!(MusicStylesPallet::unpause(RuntimeOrigin::signed(CHARLIE)));
+            run_to_block(15);
+            assert!(!MusicStylesPallet::get_styles().contains_key(&bounded(b"Rap")));
+            assert_last_event(StyleChangeEnacted { id: 0 });
+        });
+    }
+
+    #[test]
+    fn deferred_changes_are_enacted_within_the_block_limit() {
+        new_test_ext(true).execute_with(|| {
+            let max = <Test as Config>::MaxScheduledPerBlock::get();
+            for i in 0..max {
+                assert_ok!(MusicStylesPallet::update_style_name(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    None,
+                    generate_random_name(i),
+                    None
+                ));
+            }
+            System::set_block_number(2);
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+
+            // The changes due at blocks 11 and 12 are queued in this order
+            run_to_block(12);
+            assert_eq!(DeferredRange::<Test>::get(), (0, max + 1));
+            assert_eq!(DeferredChanges::<Test>::get(max), Some(max));
+
+            // The oldest ones are enacted first, the last one at the next block
+            assert_ok!(MusicStylesPallet::unpause(RuntimeOrigin::signed(CHARLIE)));
+            run_to_block(13);
+            assert_eq!(DeferredRange::<Test>::get(), (max, max + 1));
+            assert!(ScheduledChanges::<Test>::get(max - 1).is_none());
+            assert!(MusicStylesPallet::get_styles().contains_key(&bounded(b"Rock")));
+
+            run_to_block(14);
+            assert_eq!(DeferredRange::<Test>::get(), (0, 0));
+            assert!(!MusicStylesPallet::get_styles().contains_key(&bounded(b"Rock")));
+            assert_last_event(StyleChangeEnacted { id: max });
+        });
+    }
+
+    #[test]
+    fn pause_filter_only_lets_pause_calls_through() {
+        new_test_ext(true).execute_with(|| {
+            let add_call = RuntimeCall::MusicStylesPallet(crate::Call::add_style {
+                name: b"Jazz".to_vec(),
+                sub: None,
+                reason: None,
+            });
+            let unpause_call = RuntimeCall::MusicStylesPallet(crate::Call::unpause {});
+            let remark_call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+
+            assert!(PauseFilter::<Test>::contains(&add_call));
+
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+            assert!(!PauseFilter::<Test>::contains(&add_call));
+            assert!(PauseFilter::<Test>::contains(&unpause_call));
+            assert!(PauseFilter::<Test>::contains(&remark_call));
+        });
+    }
+}
+
+mod rate_limit {
+    use super::*;
+
+    #[test]
+    fn parent_styles_creation_is_rate_limited() {
+        new_test_ext(false).execute_with(|| {
+            MaxNewParentStylesPerPeriod::set(2);
+
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            assert_noop!(
+                MusicStylesPallet::add_style(RuntimeOrigin::root(), b"Jazz".to_vec(), None, None),
+                Error::<Test>::RateLimited
+            );
+
+            // Counters are reset at the start of the next period
+            run_to_block(20);
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                None,
+                None
+            ));
+        });
+    }
+
+    #[test]
+    fn sub_styles_creation_is_rate_limited() {
+        new_test_ext(true).execute_with(|| {
+            MaxNewSubStylesPerPeriod::set(3);
+
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                Some(vec![b"Bebop".to_vec(), b"Swing".to_vec()]),
+                None
+            ));
+            assert_noop!(
+                MusicStylesPallet::add_sub_style(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    vec![b"Grime".to_vec(), b"Cloud".to_vec()],
+                    None
+                ),
+                Error::<Test>::RateLimited
+            );
+            assert_noop!(
+                MusicStylesPallet::add_style(
+                    RuntimeOrigin::root(),
+                    b"Blues".to_vec(),
+                    Some(vec![b"Delta".to_vec(), b"Chicago".to_vec()]),
+                    None
+                ),
+                Error::<Test>::RateLimited
+            );
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                vec![b"Grime".to_vec()],
+                None
+            ));
+
+            run_to_block(20);
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                vec![b"Cloud".to_vec()],
+                None
+            ));
+        });
+    }
+}
+
+mod status {
+    use super::*;
+    use crate::traits::{InspectStyleStatus, StyleReferences};
+
+    #[test]
+    fn non_admin_cannot_update_status() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::set_style_status(
+                    RuntimeOrigin::signed(BOB),
+                    b"Rap".to_vec(),
+                    StyleStatus::Retired,
+                    None
+                ),
+                BadOrigin
+            );
+        });
+    }
+
+    #[test]
+    fn cannot_update_status_of_unexisting_style() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::set_style_status(
+                    RuntimeOrigin::root(),
+                    b"Jazz".to_vec(),
+                    StyleStatus::Retired,
+                    None
+                ),
+                Error::<Test>::StyleNotFound
+            );
+        });
+    }
+
+    #[test]
+    fn deprecated_style_replacement_must_exist() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::set_style_status(
+                    RuntimeOrigin::root(),
+                    b"Trap".to_vec(),
+                    StyleStatus::Deprecated(Some(bounded(b"Jazz"))),
+                    None
+                ),
+                Error::<Test>::InvalidReplacement
+            );
+            assert_noop!(
+                MusicStylesPallet::set_style_status(
+                    RuntimeOrigin::root(),
+                    b"Trap".to_vec(),
+                    StyleStatus::Deprecated(Some(bounded(b"Trap"))),
+                    None
+                ),
+                Error::<Test>::InvalidReplacement
+            );
+        });
+    }
+
+    #[test]
+    fn styles_are_active_by_default() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(
+                MusicStylesPallet::exist_with_status(&bounded(b"Drill")),
+                Some(StyleStatus::Active)
+            );
+            assert!(MusicStylesPallet::is_usable(&bounded(b"Drill")));
+            assert_eq!(
+                MusicStylesPallet::exist_with_status(&bounded(b"Jazz")),
+                None
+            );
+        });
+    }
+
+    #[test]
+    fn draft_styles_are_hidden_from_inspection() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                StyleStatus::Draft,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Drill".to_vec(),
+                StyleStatus::Draft,
+                None
+            ));
+            assert_last_event(StyleStatusUpdated {
+                name: bounded(b"Drill"),
+                status: StyleStatus::Draft,
+                who: None,
+            });
+
+            assert!(!MusicStylesPallet::exist(&bounded(b"Rock")));
+            assert!(!MusicStylesPallet::exist(&bounded(b"Drill")));
+            assert!(!MusicStylesPallet::is_sub_style(&bounded(b"Drill")));
+            assert_eq!(
+                MusicStylesPallet::exist_with_status(&bounded(b"Drill")),
+                None
+            );
+            assert_eq!(
+                MusicStylesPallet::parent_styles(),
+                vec![bounded(b"Raggae"), bounded(b"Rap")]
+            );
+
+            // Admin queries still see the drafts
+            let styles = MusicStylesPallet::get_styles();
+            assert!(styles.contains_key(&bounded(b"Rock")));
+            assert!(styles
+                .get(&bounded(b"Rap"))
+                .unwrap()
+                .contains(&bounded(b"Drill")));
+        });
+    }
+
+    #[test]
+    fn sub_styles_of_draft_parent_styles_are_hidden() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Drill".to_vec(),
+                StyleStatus::Deprecated(Some(bounded(b"Hardcore"))),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                StyleStatus::Draft,
+                None
+            ));
+
+            assert_eq!(
+                MusicStylesPallet::exist_with_status(&bounded(b"Drill")),
+                None
+            );
+            assert_eq!(MusicStylesPallet::replacement(&bounded(b"Drill")), None);
+            assert_noop!(
+                MusicStylesPallet::inc_ref(&bounded(b"Drill")),
+                Error::<Test>::StyleNotUsable
+            );
+
+            // "Hardcore" is still held by "Rock"
+            assert_eq!(
+                MusicStylesPallet::exist_with_status(&bounded(b"Hardcore")),
+                Some(StyleStatus::Active)
+            );
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Hardcore")));
+        });
+    }
+
+    #[test]
+    fn deprecated_and_retired_styles_are_not_usable() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Trap".to_vec(),
+                StyleStatus::Deprecated(Some(bounded(b"Drill"))),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                StyleStatus::Retired,
+                None
+            ));
+
+            // Historical references stay valid
+            assert!(MusicStylesPallet::exist(&bounded(b"Trap")));
+            assert!(MusicStylesPallet::exist(&bounded(b"Raggae")));
+
+            assert!(!MusicStylesPallet::is_usable(&bounded(b"Trap")));
+            assert!(!MusicStylesPallet::is_usable(&bounded(b"Raggae")));
+            assert_eq!(
+                MusicStylesPallet::replacement(&bounded(b"Trap")),
+                Some(bounded(b"Drill"))
+            );
+            assert_eq!(MusicStylesPallet::replacement(&bounded(b"Raggae")), None);
+        });
+    }
+
+    #[test]
+    fn status_follows_renames_and_removals() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                StyleStatus::Retired,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Hardcore".to_vec(),
+                StyleStatus::Retired,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                b"Hip-Hop".to_vec(),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+
+            run_to_block(11);
+            assert_eq!(
+                MusicStylesPallet::status_of(&bounded(b"Hip-Hop")),
+                StyleStatus::Retired
+            );
+            assert_eq!(MusicStylesPallet::exist_with_status(&bounded(b"Rap")), None);
+
+            // "Hardcore" still exists under "Hip-Hop"
+            assert_eq!(
+                MusicStylesPallet::status_of(&bounded(b"Hardcore")),
+                StyleStatus::Retired
+            );
+        });
+    }
+
+    #[test]
+    fn renamed_style_takes_the_status_of_an_existing_name() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                vec![b"Grime".to_vec()],
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Grime".to_vec(),
+                StyleStatus::Deprecated(None),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Trap".to_vec(),
+                StyleStatus::Retired,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                b"Grime".to_vec(),
+                None
+            ));
+
+            run_to_block(11);
+            assert_eq!(
+                MusicStylesPallet::status_of(&bounded(b"Grime")),
+                StyleStatus::Deprecated(None)
+            );
+            assert_eq!(
+                MusicStylesPallet::status_of(&bounded(b"Trap")),
+                StyleStatus::Active
+            );
+
+            // The dropped status can't be restored
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 1, None),
+                Error::<Test>::NotRevertible
+            );
+        });
+    }
+}
+
+mod notifications {
+    use super::*;
+
+    #[test]
+    fn additions_are_notified() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                Some(vec![b"Bebop".to_vec()]),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                vec![b"Grime".to_vec()],
+                None
+            ));
+
+            assert_eq!(
+                Notifications::get(),
+                vec![
+                    Notification::Added(parent("Jazz")),
+                    Notification::Added(sub("Jazz", "Bebop")),
+                    Notification::Added(sub("Rap", "Grime")),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn enacted_changes_are_notified() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                b"Cloud".to_vec(),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+
+            // Nothing is notified before the enactment
+            assert!(Notifications::get().is_empty());
+
+            run_to_block(11);
+            assert_eq!(
+                Notifications::get(),
+                vec![
+                    Notification::Renamed(sub("Rap", "Trap"), sub("Rap", "Cloud")),
+                    Notification::Removed(sub("Rock", "Hardcore")),
+                    Notification::Removed(parent("Rock")),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn failed_changes_are_not_notified() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+
+            run_to_block(11);
+            assert!(Notifications::get().is_empty());
+        });
+    }
+}
+
+mod references {
+    use super::*;
+    use crate::traits::StyleReferences;
+
+    #[test]
+    fn cannot_reference_unexisting_style() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::inc_ref(&bounded(b"Jazz")),
+                Error::<Test>::StyleNotFound
+            );
+            assert_noop!(
+                MusicStylesPallet::dec_ref(&bounded(b"Rap")),
+                Error::<Test>::NotReferenced
+            );
+        });
+    }
+
+    #[test]
+    fn cannot_reference_draft_or_retired_style() {
+        new_test_ext(true).execute_with(|| {
+            for status in [StyleStatus::Draft, StyleStatus::Retired] {
+                assert_ok!(MusicStylesPallet::set_style_status(
+                    RuntimeOrigin::root(),
+                    b"Drill".to_vec(),
+                    status,
+                    None
+                ));
+                assert_noop!(
+                    MusicStylesPallet::inc_ref(&bounded(b"Drill")),
+                    Error::<Test>::StyleNotUsable
+                );
+            }
+
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Drill".to_vec(),
+                StyleStatus::Deprecated(None),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+        });
+    }
+
+    #[test]
+    fn references_are_counted() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+            assert_eq!(MusicStylesPallet::references(&bounded(b"Drill")), 2);
+
+            assert_ok!(MusicStylesPallet::dec_ref(&bounded(b"Drill")));
+            assert_ok!(MusicStylesPallet::dec_ref(&bounded(b"Drill")));
+            assert_eq!(MusicStylesPallet::references(&bounded(b"Drill")), 0);
+            assert_noop!(
+                MusicStylesPallet::dec_ref(&bounded(b"Drill")),
+                Error::<Test>::NotReferenced
+            );
+        });
+    }
+
+    #[test]
+    fn referenced_style_cannot_be_removed() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+
+            assert_noop!(
+                MusicStylesPallet::remove_style(
+                    RuntimeOrigin::root(),
+                    b"Rap".to_vec(),
+                    Some(b"Drill".to_vec()),
+                    None
+                ),
+                Error::<Test>::StyleInUse
+            );
+            assert_noop!(
+                MusicStylesPallet::remove_style(RuntimeOrigin::root(), b"Rap".to_vec(), None, None),
+                Error::<Test>::StyleInUse
+            );
+        });
+    }
+
+    #[test]
+    fn referenced_name_used_elsewhere_can_be_removed() {
+        new_test_ext(true).execute_with(|| {
+            // "Hardcore" is also a sub style of "Rap"
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Hardcore")));
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(11);
+
+            assert!(!MusicStylesPallet::get_styles().contains_key(&bounded(b"Rock")));
+            assert_eq!(MusicStylesPallet::references(&bounded(b"Hardcore")), 1);
+        });
+    }
+
+    #[test]
+    fn removal_fails_if_referenced_before_enactment() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Trap")));
+
+            run_to_block(11);
+            assert!(MusicStylesPallet::get_styles().contains_key(&bounded(b"Rap")));
+            assert_last_event(StyleChangeFailed {
+                id: 0,
+                error: Error::<Test>::StyleInUse.into(),
+            });
+        });
+    }
+
+    #[test]
+    fn forced_removal_ignores_references() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+
+            assert_noop!(
+                MusicStylesPallet::force_remove_style(
+                    RuntimeOrigin::signed(BOB),
+                    b"Rap".to_vec(),
+                    None,
+                    None
+                ),
+                BadOrigin
+            );
+            assert_ok!(MusicStylesPallet::force_remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+
+            run_to_block(11);
+            assert!(!MusicStylesPallet::get_styles().contains_key(&bounded(b"Rap")));
+            assert_eq!(MusicStylesPallet::references(&bounded(b"Drill")), 0);
+            assert!(
+                Notifications::get().contains(&Notification::Removed(StyleRef::Sub(
+                    bounded(b"Rap"),
+                    bounded(b"Drill")
+                )))
+            );
+        });
+    }
+
+    #[test]
+    fn references_follow_renames() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Rap")));
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                b"Hip-Hop".to_vec(),
+                None
+            ));
+
+            run_to_block(11);
+            assert_eq!(MusicStylesPallet::references(&bounded(b"Rap")), 0);
+            assert_eq!(MusicStylesPallet::references(&bounded(b"Hip-Hop")), 1);
+        });
+    }
+}
+
+mod runtime_api {
+    use super::*;
+
+    #[test]
+    fn children_of_a_parent_style() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(
+                MusicStylesPallet::children(b"Rap".to_vec()),
+                Some(vec![
+                    bounded(b"Drill"),
+                    bounded(b"Trap"),
+                    bounded(b"Hardcore")
+                ])
+            );
+            assert_eq!(
+                MusicStylesPallet::children(b"Raggae".to_vec()),
+                Some(vec![])
+            );
+            assert_eq!(MusicStylesPallet::children(b"Drill".to_vec()), None);
+        });
+    }
+
+    #[test]
+    fn resolve_style_names() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Rock".to_vec()),
+                Some(StyleRef::Parent(bounded(b"Rock")))
+            );
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Trap".to_vec()),
+                Some(StyleRef::Sub(bounded(b"Rap"), bounded(b"Trap")))
+            );
+            assert_eq!(MusicStylesPallet::resolve(b"Jazz".to_vec()), None);
+
+            assert!(MusicStylesPallet::exists(b"Trap".to_vec()));
+            assert!(!MusicStylesPallet::exists(b"Jazz".to_vec()));
+
+            assert_eq!(
+                MusicStylesPallet::path(b"Drill".to_vec()),
+                Some(vec![bounded(b"Rap"), bounded(b"Drill")])
+            );
+            assert_eq!(
+                MusicStylesPallet::path(b"Raggae".to_vec()),
+                Some(vec![bounded(b"Raggae")])
+            );
+
+            // Too long names can't exist
+            let long_name = vec![0x61; <MaxNameLength as Get<u32>>::get() as usize + 1];
+            assert_eq!(MusicStylesPallet::resolve(long_name), None);
+        });
+    }
+
+    #[test]
+    fn draft_styles_are_not_returned() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Drill".to_vec(),
+                StyleStatus::Draft,
+                None
+            ));
+
+            assert!(!MusicStylesPallet::exists(b"Drill".to_vec()));
+            assert_eq!(
+                MusicStylesPallet::children(b"Rap".to_vec()),
+                Some(vec![bounded(b"Trap"), bounded(b"Hardcore")])
+            );
+        });
+    }
+}
+
+mod storage_layout {
+    use super::*;
+    use crate::migrations::{
+        v0, v1,
+        v1::{LazyMigrateToV1, MigrateToV1},
+        v2,
+        v2::MigrateToV2,
+        v3,
+        v3::MigrateToV3,
+    };
+    use frame_support::traits::{
+        Contains, GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion,
+    };
+
+    #[test]
+    fn sub_styles_are_indexed() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(ParentStyles::<Test>::count(), 3);
+
+            let mut parents: Vec<MusicStyleName> =
+                SubStyleIndex::<Test>::iter_key_prefix(bounded(b"Hardcore")).collect();
+            parents.sort();
+            assert_eq!(parents, vec![bounded(b"Rap"), bounded(b"Rock")]);
+
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                vec![b"Dub".to_vec()],
+                None
+            ));
+            assert!(SubStyleIndex::<Test>::contains_key(
+                bounded(b"Dub"),
+                bounded(b"Raggae")
+            ));
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(11);
+
+            assert_eq!(ParentStyles::<Test>::count(), 2);
+            assert!(!SubStyleIndex::<Test>::contains_key(
+                bounded(b"Hardcore"),
+                bounded(b"Rap")
+            ));
+            assert!(SubStyleIndex::<Test>::contains_key(
+                bounded(b"Hardcore"),
+                bounded(b"Rock")
+            ));
+            assert_eq!(
+                SubStyleIndex::<Test>::iter_key_prefix(bounded(b"Drill")).count(),
+                0
+            );
+        });
+    }
+
+    #[test]
+    fn inspect_through_keyed_lookups() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(
+                MusicStylesPallet::parent_styles(),
+                vec![bounded(b"Raggae"), bounded(b"Rap"), bounded(b"Rock")]
+            );
+            assert_eq!(
+                MusicStylesPallet::sub_styles(),
+                vec![
+                    bounded(b"Drill"),
+                    bounded(b"Trap"),
+                    bounded(b"Hardcore"),
+                    bounded(b"Hardcore")
+                ]
+            );
+
+            assert!(MusicStylesPallet::is_parent_style(&bounded(b"Rock")));
+            assert!(!MusicStylesPallet::is_parent_style(&bounded(b"Drill")));
+            assert!(MusicStylesPallet::is_sub_style(&bounded(b"Hardcore")));
+            assert!(!MusicStylesPallet::is_sub_style(&bounded(b"Rock")));
+            assert!(MusicStylesPallet::exist(&bounded(b"Trap")));
+            assert!(!MusicStylesPallet::exist(&bounded(b"Jazz")));
+        });
+    }
+
+    #[test]
+    fn sub_styles_of_draft_parents_are_hidden() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                StyleStatus::Draft,
+                None
+            ));
+
+            assert!(!MusicStylesPallet::is_sub_style(&bounded(b"Drill")));
+            // Still visible beneath Rock
+            assert!(MusicStylesPallet::is_sub_style(&bounded(b"Hardcore")));
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Hardcore".to_vec()),
+                Some(StyleRef::Sub(bounded(b"Rock"), bounded(b"Hardcore")))
+            );
+        });
+    }
+
+    #[test]
+    fn migrate_the_styles_tree_to_v1() {
+        new_test_ext(false).execute_with(|| {
+            let mut styles = MusicStyleDB::new();
+            let subs: MusicSubStyles = vec![bounded(b"Drill"), bounded(b"Trap")]
+                .try_into()
+                .unwrap();
+            styles.try_insert(bounded(b"Rap"), subs).unwrap();
+            styles
+                .try_insert(bounded(b"Rock"), Default::default())
+                .unwrap();
+            v0::Styles::<Test>::put(styles.clone());
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+
+            MigrateToV1::<Test>::on_runtime_upgrade();
+
+            assert_eq!(MusicStylesPallet::get_styles(), styles);
+            assert!(!v0::Styles::<Test>::exists());
+            assert!(MusicStylesPallet::is_sub_style(&bounded(b"Trap")));
+            assert_eq!(MusicStylesPallet::on_chain_storage_version(), 1);
+
+            // Running it again is a no-op
+            MigrateToV1::<Test>::on_runtime_upgrade();
+            assert_eq!(MusicStylesPallet::get_styles(), styles);
+        });
+    }
+
+    #[test]
+    fn migrate_a_full_styles_tree_to_v1_across_blocks() {
+        new_test_ext(false).execute_with(|| {
+            let max_subs = <MaxSubStyles as Get<u32>>::get();
+            let mut styles = MusicStyleDB::new();
+            for i in 0..<MaxParentStyles as Get<u32>>::get() {
+                let subs: Vec<MusicStyleName> = (0..i % (max_subs + 1))
+                    .map(|j| bounded(format!("Sub {}", j).as_bytes()))
+                    .collect();
+                styles
+                    .try_insert(
+                        bounded(format!("Style {}", i).as_bytes()),
+                        BoundedVec::truncate_from(subs),
+                    )
+                    .unwrap();
+            }
+            v0::Styles::<Test>::put(styles.clone());
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+            assert_eq!(MusicStylesPallet::on_chain_storage_version(), 1);
+            assert!(MusicStylesPallet::is_migrating());
+            assert_eq!(ParentStyles::<Test>::count(), 0);
+
+            // Every mutating call waits for the end of the migration
+            let call = RuntimeCall::MusicStylesPallet(crate::Call::add_style {
+                name: b"Jazz".to_vec(),
+                sub: None,
+                reason: None,
+            });
+            assert!(!PauseFilter::<Test>::contains(&call));
+            assert_noop!(
+                MusicStylesPallet::add_style(RuntimeOrigin::root(), b"Jazz".to_vec(), None, None),
+                Error::<Test>::MigrationInProgress
+            );
+
+            // The parent styles are moved in bounded steps
+            run_to_block(2);
+            assert_eq!(ParentStyles::<Test>::count(), 64.min(styles.len() as u32));
+
+            let mut block = 2;
+            while MusicStylesPallet::is_migrating() {
+                block += 1;
+                run_to_block(block);
+            }
+            assert_eq!(block as usize - 1, (styles.len() + 63) / 64);
+            assert_eq!(MusicStylesPallet::get_styles(), styles);
+            assert!(!v0::Styles::<Test>::exists());
+            assert!(MusicStylesPallet::is_sub_style(&bounded(b"Sub 0")));
+            System::assert_has_event(mock::RuntimeEvent::MusicStylesPallet(StylesMigrated));
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Style 1".to_vec(),
+                None,
+                None
+            ));
+        });
+    }
+
+    #[test]
+    fn lazy_migration_steps_fit_the_remaining_weight() {
+        new_test_ext(false).execute_with(|| {
+            let mut styles = MusicStyleDB::new();
+            for i in 0..10 {
+                let subs = vec![bounded(b"Drill"), bounded(format!("Sub {}", i).as_bytes())];
+                styles
+                    .try_insert(
+                        bounded(format!("Style {}", i).as_bytes()),
+                        BoundedVec::truncate_from(subs),
+                    )
+                    .unwrap();
+            }
+            v0::Styles::<Test>::put(styles.clone());
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+            assert!(!v0::Styles::<Test>::exists());
+            assert_eq!(v1::OldStylesChunks::<Test>::iter().count(), 1);
+
+            // Each parent style is weighed before it is read, proof size included
+            MusicStylesPallet::on_idle(2, v1::parent_style_weight::<Test>().saturating_mul(3));
+            assert_eq!(ParentStyles::<Test>::count(), 3);
+            MusicStylesPallet::on_idle(3, Weight::zero());
+            assert_eq!(ParentStyles::<Test>::count(), 3);
+
+            run_to_block(4);
+            assert!(!MusicStylesPallet::is_migrating());
+            assert_eq!(MusicStylesPallet::get_styles(), styles);
+            assert_eq!(v1::OldStylesChunks::<Test>::iter().count(), 0);
+        });
+    }
+
+    #[test]
+    fn failed_lazy_migration_keeps_the_pallet_blocked() {
+        new_test_ext(false).execute_with(|| {
+            v0::Styles::<Test>::put(tree(&[("Rap", &["Drill"]), ("Rock", &[])]));
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+
+            let change = StyleChange::Remove {
+                style: StyleRef::Parent(bounded(b"Rock")),
+                force: false,
+            };
+            ScheduledChanges::<Test>::insert(
+                0,
+                ScheduledChange {
+                    change,
+                    when: 3,
+                    who: None,
+                    reason: None,
+                },
+            );
+            Agenda::<Test>::insert(3, BoundedVec::truncate_from(vec![0]));
+            v1::OldStylesChunks::<Test>::insert(0, BoundedVec::truncate_from(vec![0xff; 4]));
+
+            run_to_block(3);
+            assert_has_event(StylesMigrationFailed { remaining: 2 });
+            assert!(MusicStylesPallet::is_migrating());
+            assert_eq!(ParentStyles::<Test>::count(), 0);
+            assert_noop!(
+                MusicStylesPallet::add_style(RuntimeOrigin::root(), b"Jazz".to_vec(), None, None),
+                Error::<Test>::MigrationInProgress
+            );
+
+            // The scheduled changes wait for the end of the migration
+            assert_last_event(StyleChangeDeferred { id: 0 });
+            assert_eq!(DeferredChanges::<Test>::get(0), Some(0));
+        });
+    }
+
+    #[test]
+    fn undecodable_length_prefix_stops_the_lazy_migration() {
+        new_test_ext(false).execute_with(|| {
+            let styles = tree(&[("Rap", &["Drill"]), ("Rock", &[])]);
+            let mut old = styles.encode();
+            old[0] = 0xff;
+            frame_support::storage::unhashed::put_raw(&v0::Styles::<Test>::hashed_key(), &old);
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+
+            // The styles tree isn't dropped
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+            assert_has_event(StylesMigrationFailed { remaining: 0 });
+            run_to_block(2);
+            assert!(MusicStylesPallet::is_migrating());
+            assert_eq!(ParentStyles::<Test>::count(), 0);
+
+            assert_noop!(
+                MusicStylesPallet::resume_styles_migration(RuntimeOrigin::signed(DAVE), 1, 2),
+                BadOrigin
+            );
+            assert_ok!(MusicStylesPallet::resume_styles_migration(
+                RuntimeOrigin::root(),
+                1,
+                2
+            ));
+            assert_last_event(StylesMigrationResumed {
+                offset: 1,
+                remaining: 2,
+            });
+
+            run_to_block(3);
+            assert!(!MusicStylesPallet::is_migrating());
+            assert_eq!(MusicStylesPallet::get_styles(), styles);
+            assert_noop!(
+                MusicStylesPallet::resume_styles_migration(RuntimeOrigin::root(), 0, 0),
+                Error::<Test>::NoMigration
+            );
+        });
+    }
+
+    #[test]
+    fn migrate_an_empty_styles_tree_to_v1_lazily() {
+        new_test_ext(false).execute_with(|| {
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+            assert!(!MusicStylesPallet::is_migrating());
+            assert_eq!(MusicStylesPallet::on_chain_storage_version(), 1);
+
+            v0::Styles::<Test>::put(MusicStyleDB::new());
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+            assert!(MusicStylesPallet::is_migrating());
+
+            run_to_block(2);
+            assert!(!MusicStylesPallet::is_migrating());
+            assert!(!v0::Styles::<Test>::exists());
+
+            // Running it again is a no-op
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+            assert!(!MusicStylesPallet::is_migrating());
+        });
+    }
+
+    #[test]
+    fn migrate_the_scheduled_removals_to_v1() {
+        new_test_ext(true).execute_with(|| {
+            let style = StyleRef::Sub(bounded(b"Rap"), bounded(b"Drill"));
+            frame_support::storage::unhashed::put(
+                &ScheduledChanges::<Test>::hashed_key_for(0),
+                &v1::OldScheduledChange {
+                    change: v1::OldStyleChange::Remove {
+                        style: style.clone(),
+                    },
+                    when: 11u64,
+                },
+            );
+            frame_support::storage::unhashed::put(
+                &ScheduledChanges::<Test>::hashed_key_for(1),
+                &v1::OldScheduledChange {
+                    change: v1::OldStyleChange::Rename {
+                        style: StyleRef::Parent(bounded(b"Rock")),
+                        new_name: bounded(b"Rock'n'Roll"),
+                    },
+                    when: 11u64,
+                },
+            );
+            StorageVersion::new(0).put::<MusicStylesPallet>();
+
+            LazyMigrateToV1::<Test, ConstU32<64>>::on_runtime_upgrade();
+
+            let removal = v1::ScheduledChanges::<Test>::get(0).unwrap();
+            assert_eq!(
+                removal.change,
+                StyleChange::Remove {
+                    style,
+                    force: false
+                }
+            );
+            assert_eq!(removal.when, 11);
+            assert_eq!(
+                v1::ScheduledChanges::<Test>::get(1).map(|rename| rename.change),
+                Some(StyleChange::Rename {
+                    style: StyleRef::Parent(bounded(b"Rock")),
+                    new_name: bounded(b"Rock'n'Roll"),
+                })
+            );
+            assert_eq!(MusicStylesPallet::on_chain_storage_version(), 1);
+        });
+    }
+
+    #[test]
+    fn migrate_the_scheduled_changes_to_v2() {
+        new_test_ext(true).execute_with(|| {
+            let change = StyleChange::Remove {
+                style: StyleRef::Parent(bounded(b"Rock")),
+                force: false,
+            };
+            frame_support::storage::unhashed::put(
+                &ScheduledChanges::<Test>::hashed_key_for(0),
+                &v2::OldScheduledChange {
+                    change: change.clone(),
+                    when: 11u64,
+                },
+            );
+            StorageVersion::new(1).put::<MusicStylesPallet>();
+
+            MigrateToV2::<Test>::on_runtime_upgrade();
+
+            let migrated = v3::ScheduledChanges::<Test>::get(0).unwrap();
+            assert_eq!(migrated.change, change);
+            assert_eq!(migrated.when, 11);
+            assert_eq!(migrated.who, None);
+            assert_eq!(MusicStylesPallet::on_chain_storage_version(), 2);
+        });
+    }
+
+    #[test]
+    fn migrate_the_scheduled_changes_to_v3() {
+        new_test_ext(true).execute_with(|| {
+            let change = StyleChange::Remove {
+                style: StyleRef::Parent(bounded(b"Rock")),
+                force: false,
+            };
+            v3::ScheduledChanges::<Test>::insert(
+                0,
+                v3::OldScheduledChange {
+                    change: change.clone(),
+                    when: 11u64,
+                    who: Some(DAVE),
+                },
+            );
+            StorageVersion::new(2).put::<MusicStylesPallet>();
+
+            MigrateToV3::<Test>::on_runtime_upgrade();
+
+            assert_eq!(
+                MusicStylesPallet::scheduled_change(0),
+                Some(ScheduledChange {
+                    change,
+                    when: 11,
+                    who: Some(DAVE),
+                    reason: None
+                })
+            );
+            assert_eq!(MusicStylesPallet::on_chain_storage_version(), 3);
+
+            // Running it again is a no-op
+            MigrateToV3::<Test>::on_runtime_upgrade();
+            assert!(MusicStylesPallet::scheduled_change(0).is_some());
+        });
+    }
+}
+
+mod events {
+    use super::*;
+
+    #[test]
+    fn events_carry_the_signed_admin() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::signed(DAVE),
+                b"Jazz".to_vec(),
+                Some(vec![b"Bebop".to_vec()]),
+                None
+            ));
+            assert_has_event(StyleAdded {
+                name: bounded(b"Jazz"),
+                who: Some(DAVE),
+                version: 1,
+            });
+            assert_last_event(SubStyleAdded {
+                parent: bounded(b"Jazz"),
+                name: bounded(b"Bebop"),
+                who: Some(DAVE),
+                version: 2,
+            });
+
+            assert_ok!(MusicStylesPallet::set_frozen(
+                RuntimeOrigin::signed(DAVE),
+                false
+            ));
+            assert_last_event(FrozenUpdated {
+                frozen: false,
+                who: Some(DAVE),
+            });
+        });
+    }
+
+    #[test]
+    fn enacted_changes_carry_the_scheduler() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::signed(DAVE),
+                b"Rap".to_vec(),
+                Some(b"Drill".to_vec()),
+                None
+            ));
+            assert_eq!(
+                MusicStylesPallet::scheduled_change(0).unwrap().who,
+                Some(DAVE)
+            );
+
+            run_to_block(11);
+            assert_has_event(SubStyleRemoved {
+                parent: bounded(b"Rap"),
+                name: bounded(b"Drill"),
+                who: Some(DAVE),
+                version: 1,
+            });
+        });
+    }
+
+    #[test]
+    fn taxonomy_version_is_bumped_on_every_change() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 0);
+
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                vec![b"Dub".to_vec(), b"Ska".to_vec()],
+                None
+            ));
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 2);
+
+            // Other updates don't change the tree
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Dub".to_vec(),
+                StyleStatus::Retired,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                b"Rock'n'Roll".to_vec(),
+                None
+            ));
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 2);
+
+            run_to_block(11);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 3);
+        });
+    }
+}
+
+mod taxonomy {
+    use super::*;
+    use crate::traits::InspectTaxonomyVersion;
+    use sp_runtime::traits::{BlakeTwo256, Hash};
+
+    fn tree_hash() -> H256 {
+        BlakeTwo256::hash_of(&MusicStylesPallet::get_styles())
+    }
+
+    #[test]
+    fn genesis_tree_is_hashed() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(TaxonomyHash::<Test>::get(), Some(tree_hash()));
+            assert_eq!(
+                <MusicStylesPallet as InspectTaxonomyVersion>::taxonomy_version(),
+                0
+            );
+        });
+    }
+
+    #[test]
+    fn hash_follows_the_changes() {
+        new_test_ext(true).execute_with(|| {
+            let genesis_hash = MusicStylesPallet::taxonomy_hash();
+
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                vec![b"Dub".to_vec()],
+                None
+            ));
+            let hash = <MusicStylesPallet as InspectTaxonomyVersion>::taxonomy_hash();
+            assert_ne!(hash, genesis_hash);
+            assert_eq!(hash, tree_hash());
+
+            // The changed tree is hashed once, in the next block
+            assert_eq!(TaxonomyHash::<Test>::get(), None);
+            run_to_block(2);
+            assert_eq!(TaxonomyHash::<Test>::get(), Some(hash));
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                Some(b"Dub".to_vec()),
+                None
+            ));
+            run_to_block(12);
+
+            // Same tree, same hash
+            assert_eq!(MusicStylesPallet::taxonomy_hash(), genesis_hash);
+            assert_eq!(TaxonomyHash::<Test>::get(), Some(genesis_hash));
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 2);
+        });
+    }
+
+    #[test]
+    fn failed_changes_keep_the_hash() {
+        new_test_ext(true).execute_with(|| {
+            let genesis_hash = MusicStylesPallet::taxonomy_hash();
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+            run_to_block(11);
+
+            assert_eq!(MusicStylesPallet::taxonomy_hash(), genesis_hash);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 0);
+        });
+    }
+
+    #[test]
+    fn missing_hash_is_computed() {
+        new_test_ext(true).execute_with(|| {
+            TaxonomyHash::<Test>::kill();
+
+            assert_eq!(MusicStylesPallet::taxonomy_hash(), tree_hash());
+            run_to_block(2);
+            assert_eq!(TaxonomyHash::<Test>::get(), Some(tree_hash()));
+        });
+    }
+}
+
+mod history {
+    use super::*;
+
+    #[test]
+    fn past_trees_are_rebuilt() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                Some(vec![b"Bebop".to_vec(), b"Swing".to_vec()]),
+                None
+            ));
+            let with_jazz = MusicStylesPallet::get_styles();
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 3);
+
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                b"Grime".to_vec(),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(11);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 5);
+            let current = MusicStylesPallet::get_styles();
+
+            assert_eq!(MusicStylesPallet::styles_at_version(0), Some(genesis));
+            assert_eq!(MusicStylesPallet::styles_at_version(3), Some(with_jazz));
+            assert_eq!(
+                MusicStylesPallet::styles_at_version(5),
+                Some(current.clone())
+            );
+
+            // Only the removal of the parent style is reverted
+            let mut before_removal = MusicStylesPallet::styles_at_version(4).unwrap();
+            assert_eq!(
+                before_removal
+                    .remove(&bounded(b"Rock"))
+                    .unwrap()
+                    .into_inner(),
+                vec![bounded(b"Hardcore")]
+            );
+            assert_eq!(before_removal, current);
+        });
+    }
+
+    #[test]
+    fn sub_styles_keep_their_position() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                None
+            ));
+            run_to_block(11);
+
+            let past = MusicStylesPallet::styles_at_version(0).unwrap();
+            assert_eq!(past, genesis);
+            assert_eq!(
+                past.get(&bounded(b"Rap")).unwrap().clone().into_inner(),
+                vec![bounded(b"Drill"), bounded(b"Trap"), bounded(b"Hardcore")]
+            );
+        });
+    }
+
+    #[test]
+    fn future_versions_are_unknown() {
+        new_test_ext(true).execute_with(|| {
+            assert_eq!(MusicStylesPallet::styles_at_version(1), None);
+        });
+    }
+
+    #[test]
+    fn old_versions_are_pruned() {
+        new_test_ext(true).execute_with(|| {
+            MaxHistory::set(2);
+
+            for name in [b"Jazz", b"Soul", b"Funk"] {
+                assert_ok!(MusicStylesPallet::add_style(
+                    RuntimeOrigin::root(),
+                    name.to_vec(),
+                    None,
+                    None
+                ));
+            }
+
+            assert_eq!(History::<Test>::iter_keys().count(), 2);
+            assert_eq!(MusicStylesPallet::styles_at_version(0), None);
+            let past = MusicStylesPallet::styles_at_version(1).unwrap();
+            assert!(past.contains_key(&bounded(b"Jazz")));
+            assert!(!past.contains_key(&bounded(b"Soul")));
+        });
+    }
+
+    #[test]
+    fn diffs_are_inverted() {
+        let mut tree = MusicStyleDB::new();
+        tree.try_insert(bounded(b"Rap"), vec![bounded(b"Drill")].try_into().unwrap())
+            .unwrap();
+        let initial = tree.clone();
+
+        let diffs = vec![
+            TaxonomyDiff::AddSub {
+                parent: bounded(b"Rap"),
+                name: bounded(b"Trap"),
+                index: 0,
+            },
+            TaxonomyDiff::RenameSub {
+                parent: bounded(b"Rap"),
+                index: 1,
+                old_name: bounded(b"Drill"),
+                new_name: bounded(b"UK Drill"),
+            },
+            TaxonomyDiff::RenameParent {
+                old_name: bounded(b"Rap"),
+                new_name: bounded(b"Hip-Hop"),
+            },
+        ];
+        for diff in diffs.iter() {
+            assert_eq!(diff.inverse().inverse(), *diff);
+            assert_eq!(diff.apply(&mut tree), Some(()));
+        }
+        assert_eq!(
+            tree.get(&bounded(b"Hip-Hop")).unwrap().clone().into_inner(),
+            vec![bounded(b"Trap"), bounded(b"UK Drill")]
+        );
+
+        for diff in diffs.iter().rev() {
+            assert_eq!(diff.inverse().apply(&mut tree), Some(()));
+        }
+        assert_eq!(tree, initial);
+
+        // Diffs not matching the tree are rejected
+        let diff = TaxonomyDiff::RemoveSub {
+            parent: bounded(b"Rap"),
+            index: 0,
+            name: bounded(b"Trap"),
+        };
+        assert_eq!(diff.apply(&mut tree), None);
+    }
+}
+
+mod change_log {
+    use super::*;
+
+    fn reason(text: &[u8]) -> Option<ChangeReason> {
+        Some(text.to_vec().try_into().unwrap())
+    }
+
+    #[test]
+    fn additions_are_logged() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::signed(DAVE),
+                b"Jazz".to_vec(),
+                Some(vec![b"Bebop".to_vec()]),
+                reason(b"Requested by the labels")
+            ));
+            assert_ok!(MusicStylesPallet::add_sub_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                vec![b"Swing".to_vec()],
+                None
+            ));
+
+            assert_eq!(
+                MusicStylesPallet::change_log(0),
+                Some(ChangeLogEntry {
+                    operation: LogOperation::Added(StyleRef::Parent(bounded(b"Jazz"))),
+                    who: Some(DAVE),
+                    block: 1,
+                    reason: reason(b"Requested by the labels"),
+                })
+            );
+            assert_eq!(
+                MusicStylesPallet::change_log(1),
+                Some(ChangeLogEntry {
+                    operation: LogOperation::Added(StyleRef::Sub(
+                        bounded(b"Jazz"),
+                        bounded(b"Bebop")
+                    )),
+                    who: Some(DAVE),
+                    block: 1,
+                    reason: reason(b"Requested by the labels"),
+                })
+            );
+            assert_eq!(
+                MusicStylesPallet::change_log(2),
+                Some(ChangeLogEntry {
+                    operation: LogOperation::Added(StyleRef::Sub(
+                        bounded(b"Jazz"),
+                        bounded(b"Swing")
+                    )),
+                    who: None,
+                    block: 1,
+                    reason: None,
+                })
+            );
+            assert_eq!(NextLogIndex::<Test>::get(), 3);
+        });
+    }
+
+    #[test]
+    fn scheduled_changes_are_logged_at_their_enactment() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::signed(DAVE),
+                b"Raggae".to_vec(),
+                None,
+                reason(b"Duplicate of Reggae")
+            ));
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                b"Grime".to_vec(),
+                None
+            ));
+
+            // Nothing is logged before the enactment
+            assert_eq!(MusicStylesPallet::change_log(0), None);
+            run_to_block(11);
+
+            assert_eq!(
+                MusicStylesPallet::change_log(0),
+                Some(ChangeLogEntry {
+                    operation: LogOperation::Removed(StyleRef::Parent(bounded(b"Raggae"))),
+                    who: Some(DAVE),
+                    block: 11,
+                    reason: reason(b"Duplicate of Reggae"),
+                })
+            );
+            assert_eq!(
+                MusicStylesPallet::change_log(1),
+                Some(ChangeLogEntry {
+                    operation: LogOperation::Renamed {
+                        style: StyleRef::Sub(bounded(b"Rap"), bounded(b"Trap")),
+                        new_name: bounded(b"Grime"),
+                    },
+                    who: None,
+                    block: 11,
+                    reason: None,
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn failed_enactments_are_not_logged() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                reason(b"Obsolete")
+            ));
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+            run_to_block(11);
+
+            assert_eq!(NextLogIndex::<Test>::get(), 0);
+            assert_eq!(MusicStylesPallet::change_log(0), None);
+        });
+    }
+
+    #[test]
+    fn status_updates_are_logged() {
+        new_test_ext(true).execute_with(|| {
+            let status = StyleStatus::Deprecated(Some(bounded(b"Rap")));
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::signed(DAVE),
+                b"Drill".to_vec(),
+                status.clone(),
+                reason(b"Merged into Rap")
+            ));
+
+            assert_eq!(
+                MusicStylesPallet::change_log(0),
+                Some(ChangeLogEntry {
+                    operation: LogOperation::StatusUpdated {
+                        name: bounded(b"Drill"),
+                        status,
+                    },
+                    who: Some(DAVE),
+                    block: 1,
+                    reason: reason(b"Merged into Rap"),
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn old_entries_are_pruned() {
+        new_test_ext(true).execute_with(|| {
+            MaxChangeLogEntries::set(2);
+
+            for name in [b"Jazz", b"Soul", b"Funk"] {
+                assert_ok!(MusicStylesPallet::add_style(
+                    RuntimeOrigin::root(),
+                    name.to_vec(),
+                    None,
+                    None
+                ));
+            }
+
+            assert_eq!(ChangeLog::<Test>::iter_keys().count(), 2);
+            assert_eq!(MusicStylesPallet::change_log(0), None);
+            assert_eq!(
+                MusicStylesPallet::change_log(2).unwrap().operation,
+                LogOperation::Added(StyleRef::Parent(bounded(b"Funk")))
+            );
+        });
+    }
+}
+
+mod revert {
+    use super::*;
+    use crate::traits::StyleReferences;
+
+    #[test]
+    fn additions_are_reverted() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+            let genesis_hash = MusicStylesPallet::taxonomy_hash();
+
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                Some(vec![b"Bebop".to_vec(), b"Swing".to_vec()]),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::signed(DAVE),
+                3,
+                Some(b"Bad batch".to_vec().try_into().unwrap())
+            ));
+
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 6);
+            assert_eq!(MusicStylesPallet::taxonomy_hash(), genesis_hash);
+            assert_has_event(SubStyleRemoved {
+                parent: bounded(b"Jazz"),
+                name: bounded(b"Swing"),
+                who: Some(DAVE),
+                version: 4,
+            });
+            assert_has_event(SubStyleRemoved {
+                parent: bounded(b"Jazz"),
+                name: bounded(b"Bebop"),
+                who: Some(DAVE),
+                version: 5,
+            });
+            assert_last_event(StyleRemoved {
+                name: bounded(b"Jazz"),
+                who: Some(DAVE),
+                version: 6,
+            });
+            assert_eq!(
+                MusicStylesPallet::change_log(5).unwrap().reason,
+                Some(b"Bad batch".to_vec().try_into().unwrap())
+            );
+        });
+    }
+
+    #[test]
+    fn renames_and_removals_are_reverted() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+            let genesis_hash = MusicStylesPallet::taxonomy_hash();
+
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Trap".to_vec()),
+                b"Grime".to_vec(),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Drill".to_vec()),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(11);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 3);
+
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                3,
+                None
+            ));
+
+            // Sub styles are restored at their position
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_eq!(MusicStylesPallet::taxonomy_hash(), genesis_hash);
+            assert_has_event(SubStyleNameUpdated {
+                parent: bounded(b"Rap"),
+                old_name: bounded(b"Grime"),
+                new_name: bounded(b"Trap"),
+                who: None,
+                version: 7,
+            });
+        });
+    }
+
+    #[test]
+    fn reverting_nothing_is_a_no_op() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                0,
+                None
+            ));
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 0);
+        });
+    }
+
+    #[test]
+    fn unknown_history_cannot_be_reverted() {
+        new_test_ext(true).execute_with(|| {
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 1, None),
+                Error::<Test>::NotRevertible
+            );
+
+            MaxHistory::set(1);
+            for name in [b"Jazz", b"Soul"] {
+                assert_ok!(MusicStylesPallet::add_style(
+                    RuntimeOrigin::root(),
+                    name.to_vec(),
+                    None,
+                    None
+                ));
+            }
+
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 2, None),
+                Error::<Test>::NotRevertible
+            );
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                1,
+                None
+            ));
+            assert!(!MusicStylesPallet::get_styles().contains_key(&bounded(b"Soul")));
+        });
+    }
+
+    #[test]
+    fn cannot_revert_more_than_the_history() {
+        new_test_ext(true).execute_with(|| {
+            MaxHistory::set(1);
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                None,
+                None
+            ));
+
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), u32::MAX, None),
+                Error::<Test>::NotRevertible
+            );
+        });
+    }
+
+    #[test]
+    fn removals_dropping_a_status_cannot_be_reverted() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::set_style_status(
+                RuntimeOrigin::root(),
+                b"Drill".to_vec(),
+                StyleStatus::Deprecated(None),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                Some(b"Drill".to_vec()),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(11);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 2);
+
+            // The removal of Raggae dropped nothing, the one of Drill dropped its status
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                1,
+                None
+            ));
+            assert!(MusicStylesPallet::get_styles().contains_key(&bounded(b"Raggae")));
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Raggae".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(21);
+
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 4, None),
+                Error::<Test>::NotRevertible
+            );
+        });
+    }
+
+    #[test]
+    fn diverging_tree_cannot_be_reverted() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                None,
+                None
+            ));
+
+            // The tree is changed without going through the history
+            let mut styles = MusicStylesPallet::get_styles();
+            styles.remove(&bounded(b"Jazz"));
+            MusicStylesPallet::put_styles(styles);
+
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 1, None),
+                Error::<Test>::NotRevertible
+            );
+        });
+    }
+
+    #[test]
+    fn restored_styles_are_not_rate_limited() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Rap".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(11);
+            MaxNewParentStylesPerPeriod::set(0);
+            MaxNewSubStylesPerPeriod::set(0);
+
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                1,
+                None
+            ));
+            assert_eq!(
+                MusicStylesPallet::get_styles().get(&bounded(b"Rap")),
+                Some(&BoundedVec::truncate_from(vec![
+                    bounded(b"Drill"),
+                    bounded(b"Trap"),
+                    bounded(b"Hardcore")
+                ]))
+            );
+            assert_eq!(NewParentStyles::<Test>::get(), 0);
+        });
+    }
+
+    #[test]
+    fn reverts_follow_the_usual_checks() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::add_style(
+                RuntimeOrigin::root(),
+                b"Jazz".to_vec(),
+                None,
+                None
+            ));
+
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::signed(BOB), 1, None),
+                BadOrigin
+            );
+
+            assert_ok!(<MusicStylesPallet as StyleReferences>::inc_ref(&bounded(
+                b"Jazz"
+            )));
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 1, None),
+                Error::<Test>::StyleInUse
+            );
+
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+            assert_noop!(
+                MusicStylesPallet::revert_changes(RuntimeOrigin::root(), 1, None),
+                Error::<Test>::StyleFrozen
+            );
+        });
+    }
+}
+
+mod genesis {
+    use super::*;
+    use frame_support::traits::BuildGenesisConfig;
+    use sp_runtime::BuildStorage;
+
+    fn config(styles: Vec<(&str, Vec<&str>)>) -> crate::GenesisConfig<Test> {
+        crate::GenesisConfig {
+            styles: styles
+                .into_iter()
+                .map(|(parent, subs)| GenesisStyle::new(parent, subs))
+                .collect(),
+            phantom: Default::default(),
+        }
+    }
+
+    #[test]
+    fn valid_styles_are_built() {
+        let styles = config(vec![("Rap", vec!["Drill", "Trap"]), ("Rock", vec!["Trap"])])
+            .build_styles()
+            .unwrap();
+
+        assert_eq!(styles.len(), 2);
+        assert_eq!(
+            styles.get(&bounded(b"Rap")).unwrap().clone().into_inner(),
+            vec![bounded(b"Drill"), bounded(b"Trap")]
+        );
+    }
+
+    #[test]
+    fn invalid_entries_are_reported() {
+        let long_name = "a".repeat(<MaxNameLength as Get<u32>>::get() as usize + 1);
+
+        assert_eq!(
+            config(vec![(long_name.as_str(), vec![])]).build_styles(),
+            Err(GenesisError::NameTooLong(long_name.clone().into_bytes()))
+        );
+        assert_eq!(
+            config(vec![("Rap", vec![long_name.as_str()])]).build_styles(),
+            Err(GenesisError::NameTooLong(long_name.into_bytes()))
+        );
+        assert_eq!(
+            config(vec![("Rap", vec![]), ("Rap", vec!["Drill"])]).build_styles(),
+            Err(GenesisError::DuplicateStyle(b"Rap".to_vec()))
+        );
+        assert_eq!(
+            config(vec![("Rap", vec!["Drill", "Drill"])]).build_styles(),
+            Err(GenesisError::DuplicateSubStyle(
+                b"Rap".to_vec(),
+                b"Drill".to_vec()
+            ))
+        );
+    }
+
+    #[test]
+    fn capacity_overflows_are_reported() {
+        let subs: Vec<String> = (0..=<MaxSubStyles as Get<u32>>::get())
+            .map(|i| format!("Sub {}", i))
+            .collect();
+        assert_eq!(
+            config(vec![("Rap", subs.iter().map(|sub| sub.as_str()).collect())]).build_styles(),
+            Err(GenesisError::TooManySubStyles(b"Rap".to_vec()))
+        );
+
+        let parents: Vec<String> = (0..=MaxParentStyles::get())
+            .map(|i| format!("Style {}", i))
+            .collect();
+        assert_eq!(
+            config(
+                parents
+                    .iter()
+                    .map(|parent| (parent.as_str(), vec![]))
+                    .collect()
+            )
+            .build_styles(),
+            Err(GenesisError::TooManyStyles(
+                format!("Style {}", MaxParentStyles::get()).into_bytes()
+            ))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid music styles genesis: Drill is listed twice under Rap")]
+    fn invalid_genesis_panics_with_the_offending_names() {
+        sp_io::TestExternalities::default().execute_with(|| {
+            config(vec![("Rap", vec!["Drill", "Drill"])]).build();
+        });
+    }
+
+    const CHAIN_SPEC: &str = r#"{
+        "styles": {
+            "Rap": ["Drill", "Trap"],
+            "Rock": { "subs": ["Hardcore"], "aliases": ["Rock'n'Roll"] },
+            "Raggae": { "status": { "deprecated": "Rock" } }
+        },
+        "phantom": null
+    }"#;
+
+    fn chain_spec_styles() -> Vec<GenesisStyle> {
+        vec![
+            GenesisStyle::new("Rap", vec!["Drill", "Trap"]),
+            GenesisStyle {
+                aliases: vec![b"Rock'n'Roll".to_vec()],
+                ..GenesisStyle::new("Rock", vec!["Hardcore"])
+            },
+            GenesisStyle {
+                status: Some(StyleStatus::Deprecated(Some(bounded(b"Rock")))),
+                ..GenesisStyle::new("Raggae", vec![])
+            },
+        ]
+    }
+
+    #[test]
+    fn object_form_is_read() {
+        let config: crate::GenesisConfig<Test> = serde_json::from_str(CHAIN_SPEC).unwrap();
+
+        assert_eq!(config.styles, chain_spec_styles());
+    }
+
+    #[test]
+    fn list_form_is_read() {
+        let config: crate::GenesisConfig<Test> = serde_json::from_str(
+            r#"{
+                "styles": [
+                    [[82, 97, 112], [[68, 114, 105, 108, 108], "Trap"]],
+                    { "name": "Rock", "subs": ["Hardcore"], "aliases": ["Rock'n'Roll"] },
+                    { "name": "Raggae", "status": { "deprecated": "Rock" } }
+                ],
+                "phantom": null
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.styles, chain_spec_styles());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(serde_json::from_str::<crate::GenesisConfig<Test>>(
+            r#"{ "styles": { "Rap": { "sub": ["Drill"] } }, "phantom": null }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn styles_are_written_as_an_object() {
+        let config = crate::GenesisConfig::<Test> {
+            styles: chain_spec_styles(),
+            phantom: Default::default(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::from_str::<serde_json::Value>(CHAIN_SPEC).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_names_round_trip() {
+        let config = crate::GenesisConfig::<Test> {
+            styles: vec![
+                GenesisStyle::new(vec![0xff, 0x61], vec![vec![0x61]]),
+                GenesisStyle {
+                    status: Some(StyleStatus::Retired),
+                    ..GenesisStyle::new("Rap", vec![])
+                },
+            ],
+            phantom: Default::default(),
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.starts_with(r#"{"styles":[{"name":[255,97],"subs":["a"]}"#));
+
+        let read: crate::GenesisConfig<Test> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.styles, config.styles);
+    }
+
+    #[test]
+    fn chain_spec_is_assimilated() {
+        let config: crate::GenesisConfig<Test> = serde_json::from_str(CHAIN_SPEC).unwrap();
+
+        sp_io::TestExternalities::new(config.build_storage().unwrap()).execute_with(|| {
+            assert_eq!(
+                MusicStylesPallet::get_styles(),
+                config.build_styles().unwrap()
+            );
+            assert_eq!(
+                MusicStylesPallet::status_of(&bounded(b"Raggae")),
+                StyleStatus::Deprecated(Some(bounded(b"Rock")))
+            );
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Rock'n'Roll".to_vec()),
+                Some(StyleRef::Parent(bounded(b"Rock")))
+            );
+        });
+    }
+
+    #[test]
+    fn invalid_metadata_is_reported() {
+        let mut styles = chain_spec_styles();
+        styles[2].status = Some(StyleStatus::Deprecated(Some(bounded(b"Jazz"))));
+        let config = crate::GenesisConfig::<Test> {
+            styles,
+            phantom: Default::default(),
+        };
+        assert_eq!(
+            config.build_metadata(&config.build_styles().unwrap()),
+            Err(GenesisError::InvalidReplacement(b"Raggae".to_vec()))
+        );
+
+        let mut styles = chain_spec_styles();
+        styles[0].aliases = vec![b"Trap".to_vec()];
+        let config = crate::GenesisConfig::<Test> {
+            styles,
+            phantom: Default::default(),
+        };
+        assert_eq!(
+            config.build_metadata(&config.build_styles().unwrap()),
+            Err(GenesisError::AliasConflict(b"Trap".to_vec()))
+        );
+    }
+
+    #[test]
+    fn aliases_follow_their_style() {
+        let config: crate::GenesisConfig<Test> = serde_json::from_str(CHAIN_SPEC).unwrap();
+
+        sp_io::TestExternalities::new(config.build_storage().unwrap()).execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(MusicStylesPallet::update_style_name(
+                RuntimeOrigin::root(),
+                b"Rock".to_vec(),
+                None,
+                b"Metal".to_vec(),
+                None
+            ));
+            run_to_block(11);
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Rock'n'Roll".to_vec()),
+                Some(StyleRef::Parent(bounded(b"Metal")))
+            );
+
+            assert_ok!(MusicStylesPallet::remove_style(
+                RuntimeOrigin::root(),
+                b"Metal".to_vec(),
+                None,
+                None
+            ));
+            run_to_block(21);
+            assert_eq!(MusicStylesPallet::resolve(b"Rock'n'Roll".to_vec()), None);
+            assert_eq!(StyleAliases::<Test>::iter().count(), 0);
+        });
+    }
+}
+
+#[cfg(feature = "default-taxonomy")]
+mod default_taxonomy {
+    use super::*;
+    use crate::taxonomy::DEFAULT_TAXONOMY;
+    use allfeat_support::types::music::style::{MaxNameLength, MaxSubStyles};
+    use sp_runtime::BuildStorage;
+
+    #[test]
+    fn default_taxonomy_fits_within_the_bounds() {
+        assert!(DEFAULT_TAXONOMY.len() as u32 <= <MaxParentStyles as Get<u32>>::get());
+
+        for (parent, subs) in DEFAULT_TAXONOMY {
+            assert!(subs.len() as u32 <= <MaxSubStyles as Get<u32>>::get());
+            for name in sp_std::iter::once(parent).chain(subs.iter()) {
+                assert!(name.len() as u32 <= <MaxNameLength as Get<u32>>::get());
+            }
+        }
+
+        let total: usize = DEFAULT_TAXONOMY
+            .iter()
+            .map(|(_, subs)| subs.len() + 1)
+            .sum();
+        assert!(total >= 200);
+    }
+
+    #[test]
+    fn default_taxonomy_is_valid() {
+        let config = crate::GenesisConfig::<Test>::with_default_taxonomy();
+        let styles = config.build_styles().unwrap();
+        assert_eq!(styles.len(), DEFAULT_TAXONOMY.len());
+        assert_eq!(config.build_metadata(&styles).unwrap(), Default::default());
+    }
+
+    #[test]
+    fn default_taxonomy_builds_the_genesis() {
+        let storage = crate::GenesisConfig::<Test>::with_default_taxonomy()
+            .build_storage()
+            .unwrap();
+
+        sp_io::TestExternalities::from(storage).execute_with(|| {
+            assert_eq!(
+                MusicStylesPallet::children(b"Rock".to_vec()).map(|subs| subs.len()),
+                Some(14)
+            );
+            assert_eq!(
+                MusicStylesPallet::resolve(b"Psytrance".to_vec()),
+                Some(StyleRef::Sub(bounded(b"Trance"), bounded(b"Psytrance")))
+            );
+        });
+    }
+}
+mod io {
+    use super::*;
+    use crate::io::{from_csv, from_json, to_csv, to_json, IoError, IoErrorKind};
+
+    fn error(line: usize, kind: IoErrorKind) -> Result<MusicStyleDB, IoError> {
+        Err(IoError { line, kind })
+    }
+
+    #[test]
+    fn csv_round_trips() {
+        let styles = tree(&[
+            ("Rap", &["Drill", "Trap"]),
+            ("Rock, Paper", &["\"Hard\" Rock"]),
+            ("Raggae", &[]),
+        ]);
+
+        let csv = to_csv(&styles).unwrap();
+        assert_eq!(
+            csv,
+            "parent,sub\nRaggae,\nRap,Drill\nRap,Trap\n\"Rock, Paper\",\"\"\"Hard\"\" Rock\"\n"
+        );
+        assert_eq!(from_csv::<Test>(&csv).unwrap(), styles);
+    }
+
+    #[test]
+    fn csv_rows_are_merged_under_their_parent() {
+        let input = "Rap,Drill\r\nRock\n\nRap,Trap\nRock,Hardcore\n";
+
+        assert_eq!(
+            from_csv::<Test>(input).unwrap(),
+            tree(&[("Rap", &["Drill", "Trap"]), ("Rock", &["Hardcore"])])
+        );
+    }
+
+    #[test]
+    fn csv_errors_are_line_numbered() {
+        let long_name = "a".repeat(<MaxNameLength as Get<u32>>::get() as usize + 1);
+
+        assert_eq!(
+            from_csv::<Test>("parent,sub\nRap,Drill\nRap,Drill\n"),
+            error(
+                3,
+                IoErrorKind::Invalid(GenesisError::DuplicateSubStyle(
+                    b"Rap".to_vec(),
+                    b"Drill".to_vec()
+                ))
+            )
+        );
+        assert_eq!(
+            from_csv::<Test>(&format!("Rap\n{},Drill", long_name)),
+            error(
+                2,
+                IoErrorKind::Invalid(GenesisError::NameTooLong(long_name.into_bytes()))
+            )
+        );
+        assert_eq!(
+            from_csv::<Test>("Rap\nRap,Trap,Drill"),
+            error(
+                2,
+                IoErrorKind::Syntax(
+                    "expected a parent style and an optional sub style, found 3 fields".into()
+                )
+            )
+        );
+        assert_eq!(
+            from_csv::<Test>("\n,Trap"),
+            error(2, IoErrorKind::Syntax("missing parent style".into()))
+        );
+        assert_eq!(
+            from_csv::<Test>("\"Rap,Trap"),
+            error(1, IoErrorKind::Syntax("unterminated quoted field".into()))
+        );
+
+        let too_many: String = (0..=<MaxParentStyles as Get<u32>>::get())
+            .map(|i| format!("Style {}\n", i))
+            .collect();
+        assert_eq!(
+            from_csv::<Test>(&too_many).unwrap_err().line,
+            <MaxParentStyles as Get<u32>>::get() as usize + 1
+        );
+    }
+
+    #[test]
+    fn csv_export_rejects_unwritable_names() {
+        let mut styles = tree(&[("Rap", &["Drill"])]);
+        styles
+            .try_insert(bounded(&[0xff]), Default::default())
+            .unwrap();
+
+        assert_eq!(
+            to_csv(&styles),
+            Err(IoError {
+                line: 3,
+                kind: IoErrorKind::NotWritable(vec![0xff])
+            })
+        );
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let styles = tree(&[("Rap", &["Drill", "Trap"]), ("Raggae", &[])]);
+
+        let json = to_json(&styles);
+        assert_eq!(
+            json,
+            "{\n  \"Raggae\": [],\n  \"Rap\": [\n    \"Drill\",\n    \"Trap\"\n  ]\n}"
+        );
+        assert_eq!(from_json::<Test>(&json).unwrap(), styles);
+    }
+
+    #[test]
+    fn json_errors_are_line_numbered() {
+        assert_eq!(
+            from_json::<Test>("{\n  \"Rap\": [\"Drill\"],\n  \"Rap\": []\n}"),
+            error(
+                3,
+                IoErrorKind::Invalid(GenesisError::DuplicateStyle(b"Rap".to_vec()))
+            )
+        );
+        assert_eq!(
+            from_json::<Test>("{\n  \"Rock\": { \"aliases\": [\"Rock'n'Roll\"] }\n}"),
+            error(2, IoErrorKind::Metadata(b"Rock".to_vec()))
+        );
+
+        let syntax = from_json::<Test>("{\n  \"Rap\": [\"Drill\",]\n}").unwrap_err();
+        assert_eq!(syntax.line, 2);
+        assert!(matches!(syntax.kind, IoErrorKind::Syntax(_)));
+        assert!(!syntax.to_string().contains("column"));
+    }
+}
+mod diff {
+    use super::*;
+    use crate::diff;
+
+    /// Apply the changes one after the other, the order of the sub styles aside
+    fn assert_turns_into(old: &MusicStyleDB, changes: &[StyleChange], new: &MusicStyleDB) {
+        let sorted = |tree: &MusicStyleDB| -> Vec<(MusicStyleName, Vec<MusicStyleName>)> {
+            tree.iter()
+                .map(|(parent, subs)| {
+                    let mut subs = subs.to_vec();
+                    subs.sort();
+                    (parent.clone(), subs)
+                })
+                .collect()
+        };
+
+        let mut tree = old.clone();
+        for change in changes {
+            assert_eq!(change.apply(&mut tree), Some(()), "{:?}", change);
+        }
+        assert_eq!(sorted(&tree), sorted(new));
+    }
+
+    #[test]
+    fn identical_trees_have_no_changes() {
+        let styles = tree(&[("Rap", &["Drill", "Trap"]), ("Rock", &[])]);
+
+        assert_eq!(diff(&styles, &styles), vec![]);
+    }
+
+    #[test]
+    fn additions_and_removals_are_found() {
+        let old = tree(&[("Rap", &["Drill", "Trap"]), ("Rock", &["Hardcore"])]);
+        let new = tree(&[("Jazz", &["Bebop"]), ("Rap", &["Drill", "Grime"])]);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                StyleChange::Remove {
+                    style: parent("Rock"),
+                    force: false
+                },
+                StyleChange::Remove {
+                    style: sub("Rap", "Trap"),
+                    force: false
+                },
+                StyleChange::Add {
+                    style: parent("Jazz")
+                },
+                StyleChange::Add {
+                    style: sub("Jazz", "Bebop")
+                },
+                StyleChange::Add {
+                    style: sub("Rap", "Grime")
+                },
+            ]
+        );
+        assert_turns_into(&old, &changes, &new);
+    }
+
+    #[test]
+    fn sub_styles_are_moved_between_parents() {
+        let old = tree(&[("Rap", &["Drill", "Trap"]), ("Rock", &["Hardcore"])]);
+        let new = tree(&[("Rap", &["Drill"]), ("Rock", &["Hardcore", "Trap"])]);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![StyleChange::Move {
+                style: sub("Rap", "Trap"),
+                new_parent: bounded(b"Rock")
+            }]
+        );
+        assert_turns_into(&old, &changes, &new);
+    }
+
+    #[test]
+    fn sub_styles_are_moved_out_before_their_parent_removal() {
+        let old = tree(&[("Rap", &["Drill", "Trap", "Grime"])]);
+        let new = tree(&[("Urban", &["Drill"])]);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                StyleChange::Add {
+                    style: parent("Urban")
+                },
+                StyleChange::Move {
+                    style: sub("Rap", "Drill"),
+                    new_parent: bounded(b"Urban")
+                },
+                StyleChange::Remove {
+                    style: parent("Rap"),
+                    force: false
+                },
+            ]
+        );
+        assert_turns_into(&old, &changes, &new);
+    }
+
+    #[test]
+    fn similar_names_are_renamed() {
+        let old = tree(&[("Raggae", &[]), ("Rap", &["Hip Hop", "Trap"])]);
+        let new = tree(&[("Rap", &["Hip-Hop", "Jazz"]), ("Reggae", &[])]);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                StyleChange::Remove {
+                    style: sub("Rap", "Trap"),
+                    force: false
+                },
+                StyleChange::Rename {
+                    style: parent("Raggae"),
+                    new_name: bounded(b"Reggae")
+                },
+                StyleChange::Rename {
+                    style: sub("Rap", "Hip Hop"),
+                    new_name: bounded(b"Hip-Hop")
+                },
+                StyleChange::Add {
+                    style: sub("Rap", "Jazz")
+                },
+            ]
+        );
+        assert_turns_into(&old, &changes, &new);
+    }
+
+    #[test]
+    fn parents_keeping_their_sub_styles_are_renamed() {
+        let old = tree(&[("Rap", &["Drill", "Trap", "Grime"])]);
+        let new = tree(&[("Urban", &["Drill", "Trap", "Jersey"])]);
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                StyleChange::Remove {
+                    style: sub("Rap", "Grime"),
+                    force: false
+                },
+                StyleChange::Rename {
+                    style: parent("Rap"),
+                    new_name: bounded(b"Urban")
+                },
+                StyleChange::Add {
+                    style: sub("Urban", "Jersey")
+                },
+            ]
+        );
+        assert_turns_into(&old, &changes, &new);
+
+        // Less than half of the sub styles are kept
+        let new = tree(&[("Urban", &["Drill", "Jersey"])]);
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 4);
+        assert_turns_into(&old, &changes, &new);
+    }
+
+    #[test]
+    fn full_trees_are_diffed_within_their_bounds() {
+        let max_parents = <MaxParentStyles as Get<u32>>::get();
+        let max_subs = <MaxSubStyles as Get<u32>>::get();
+        let name = |name: String| -> MusicStyleName { bounded(name.as_bytes()) };
+        let full_tree = |styles: Vec<(String, Vec<String>)>| {
+            let mut tree = MusicStyleDB::new();
+            for (parent, subs) in styles {
+                let subs = subs.into_iter().map(name).collect::<Vec<_>>();
+                tree.try_insert(name(parent), subs.try_into().unwrap())
+                    .unwrap();
+            }
+            tree
+        };
+        let full: Vec<String> = (0..max_subs).map(|i| format!("Full {i}")).collect();
+
+        // "Old 0" moves its sub style to a kept parent style and "Old 1" to an added one, the
+        // full "Old 3" receives a sub style from "Old 4" and gives one to "Old 5"
+        let old = full_tree(
+            (0..max_parents)
+                .map(|i| {
+                    let subs = match i {
+                        1 => vec!["Sub 1".into(), "Gone 1".into(), "Gone 2".into()],
+                        3 => full.clone(),
+                        4 => vec!["Sub 4".into(), "Extra 4".into()],
+                        _ => vec![format!("Sub {i}")],
+                    };
+                    (format!("Old {i}"), subs)
+                })
+                .collect(),
+        );
+        let new = full_tree(
+            (2..max_parents)
+                .map(|i| {
+                    let subs = match i {
+                        2 => vec!["Sub 2".into(), "Sub 0".into()],
+                        3 => full[1..]
+                            .iter()
+                            .cloned()
+                            .chain(["Extra 4".into()])
+                            .collect(),
+                        4 => vec!["Sub 4".into()],
+                        5 => vec!["Sub 5".into(), "Full 0".into()],
+                        _ => vec![format!("Sub {i}")],
+                    };
+                    (format!("Old {i}"), subs)
+                })
+                .chain([
+                    ("New 0".into(), vec!["Sub 1".into()]),
+                    ("New 1".into(), vec!["Fresh".into()]),
+                ])
+                .collect(),
+        );
+        assert_eq!(old.len() as u32, max_parents);
+        assert_eq!(new.len() as u32, max_parents);
+
+        let mut replayed = old.clone();
+        for change in diff(&old, &new) {
+            let taxonomy_diff = change
+                .to_diff(&replayed)
+                .expect("the change matches the tree");
+            assert_eq!(taxonomy_diff.apply(&mut replayed), Some(()), "{:?}", change);
+        }
+        assert_eq!(replayed, new);
+    }
+
+    #[test]
+    fn random_trees_are_diffed() {
+        let mut rng = thread_rng();
+        let parents = ["Blues", "Funk", "Jazz", "Metal", "Rap", "Rock"];
+        let subs = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let mut random_tree = || {
+            let mut tree = MusicStyleDB::new();
+            for parent in parents.iter().filter(|_| rng.gen_bool(0.6)) {
+                let subs: Vec<MusicStyleName> = subs
+                    .iter()
+                    .filter(|_| rng.gen_bool(0.3))
+                    .map(|sub| bounded(sub.as_bytes()))
+                    .collect();
+                tree.try_insert(bounded(parent.as_bytes()), BoundedVec::truncate_from(subs))
+                    .unwrap();
+            }
+            tree
+        };
+
+        for _ in 0..200 {
+            let (old, new) = (random_tree(), random_tree());
+            let changes = diff(&old, &new);
+            assert_turns_into(&old, &changes, &new);
+        }
+    }
+
+    #[test]
+    fn moves_are_enacted_and_reverted() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+            Notifications::set(vec![]);
+
+            assert_ok!(MusicStylesPallet::enact_change(
+                StyleChange::Move {
+                    style: sub("Rap", "Drill"),
+                    new_parent: bounded(b"Rock"),
+                },
+                Some(DAVE),
+                None
+            ));
+
+            assert_eq!(
+                MusicStylesPallet::children(b"Rock".to_vec()),
+                Some(vec![bounded(b"Hardcore"), bounded(b"Drill")])
+            );
+            assert_eq!(
+                MusicStylesPallet::parents_of(&bounded(b"Drill")),
+                vec![bounded(b"Rock")]
+            );
+            assert_last_event(SubStyleMoved {
+                parent: bounded(b"Rap"),
+                new_parent: bounded(b"Rock"),
+                name: bounded(b"Drill"),
+                who: Some(DAVE),
+                version: 1,
+            });
+            assert_eq!(
+                Notifications::get(),
+                vec![Notification::Moved(
+                    sub("Rap", "Drill"),
+                    sub("Rock", "Drill")
+                )]
+            );
+            assert_eq!(
+                MusicStylesPallet::taxonomy_diff(0, 1),
+                Some(vec![StyleChange::Move {
+                    style: sub("Rap", "Drill"),
+                    new_parent: bounded(b"Rock"),
+                }])
+            );
+
+            // The sub style gets back to its position
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                1,
+                None
+            ));
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+        });
+    }
+
+    #[test]
+    fn invalid_moves_are_rejected() {
+        new_test_ext(true).execute_with(|| {
+            let enact = |style, new_parent: &[u8]| {
+                MusicStylesPallet::enact_change(
+                    StyleChange::Move {
+                        style,
+                        new_parent: bounded(new_parent),
+                    },
+                    None,
+                    None,
+                )
+            };
+
+            assert_noop!(enact(parent("Rap"), b"Rock"), Error::<Test>::InvalidMove);
+            assert_noop!(
+                enact(sub("Rap", "Drill"), b"Jazz"),
+                Error::<Test>::InvalidMove
+            );
+            assert_noop!(
+                enact(sub("Rap", "Drill"), b"Rap"),
+                Error::<Test>::InvalidMove
+            );
+            assert_noop!(
+                enact(sub("Rap", "Hardcore"), b"Rock"),
+                Error::<Test>::NameAlreadyExists
+            );
+            assert_noop!(
+                enact(sub("Rap", "Jazz"), b"Rock"),
+                Error::<Test>::StyleNotFound
+            );
+        });
+    }
+}
+mod set_taxonomy {
+    use super::*;
+    use crate::traits::StyleReferences;
+
+    #[test]
+    fn taxonomy_is_replaced_with_granular_events() {
+        new_test_ext(true).execute_with(|| {
+            let new = tree(&[
+                ("Rap", &["Drill", "Grime"]),
+                ("Reggae", &[]),
+                ("Rock", &["Hardcore", "Trap"]),
+            ]);
+
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::signed(DAVE),
+                new.clone(),
+                None
+            ));
+
+            assert_eq!(MusicStylesPallet::get_styles(), new);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 4);
+            assert_has_event(SubStyleRemoved {
+                parent: bounded(b"Rap"),
+                name: bounded(b"Hardcore"),
+                who: Some(DAVE),
+                version: 1,
+            });
+            assert_has_event(StyleNameUpdated {
+                old_name: bounded(b"Raggae"),
+                new_name: bounded(b"Reggae"),
+                who: Some(DAVE),
+                version: 2,
+            });
+            assert_has_event(SubStyleMoved {
+                parent: bounded(b"Rap"),
+                new_parent: bounded(b"Rock"),
+                name: bounded(b"Trap"),
+                who: Some(DAVE),
+                version: 3,
+            });
+            assert_last_event(SubStyleAdded {
+                parent: bounded(b"Rap"),
+                name: bounded(b"Grime"),
+                who: Some(DAVE),
+                version: 4,
+            });
+
+            // Nothing changes when the tree is already up to date
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::root(),
+                new,
+                None
+            ));
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 4);
+        });
+    }
+
+    #[test]
+    fn full_taxonomy_is_replaced() {
+        new_test_ext(true).execute_with(|| {
+            let mut styles = MusicStyleDB::new();
+            for i in 0..<MaxParentStyles as Get<u32>>::get() {
+                let subs = vec![bounded(format!("Sub {i}").as_bytes())];
+                styles
+                    .try_insert(
+                        bounded(format!("Style {i}").as_bytes()),
+                        subs.try_into().unwrap(),
+                    )
+                    .unwrap();
+            }
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::root(),
+                styles.clone(),
+                None
+            ));
+
+            // "Style 0" moves its sub style out before making room for "Jazz"
+            styles.remove(&bounded(b"Style 0"));
+            styles
+                .get_mut(&bounded(b"Style 1"))
+                .unwrap()
+                .try_push(bounded(b"Sub 0"))
+                .unwrap();
+            styles
+                .try_insert(bounded(b"Jazz"), Default::default())
+                .unwrap();
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::root(),
+                styles.clone(),
+                None
+            ));
+
+            assert_eq!(MusicStylesPallet::get_styles(), styles);
+            assert_has_event(SubStyleMoved {
+                parent: bounded(b"Style 0"),
+                new_parent: bounded(b"Style 1"),
+                name: bounded(b"Sub 0"),
+                who: None,
+                version: MusicStylesPallet::taxonomy_version() - 2,
+            });
+        });
+    }
+
+    #[test]
+    fn sub_styles_order_is_kept() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+            let new = tree(&[
+                ("Rap", &["Grime", "Drill"]),
+                ("Reggae", &[]),
+                ("Rock", &["Trap", "Hardcore"]),
+            ]);
+
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::signed(DAVE),
+                new.clone(),
+                None
+            ));
+
+            assert_eq!(MusicStylesPallet::get_styles(), new);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 6);
+            assert_eq!(
+                History::<Test>::get(5),
+                Some(TaxonomyDiff::ReorderSubs {
+                    parent: bounded(b"Rap"),
+                    old_subs: BoundedVec::truncate_from(vec![bounded(b"Drill"), bounded(b"Grime")]),
+                    new_subs: BoundedVec::truncate_from(vec![bounded(b"Grime"), bounded(b"Drill")]),
+                })
+            );
+            assert_last_event(SubStylesReordered {
+                parent: bounded(b"Rock"),
+                who: Some(DAVE),
+                version: 6,
+            });
+
+            // The history replays the intermediate trees
+            assert_eq!(
+                MusicStylesPallet::styles_at_version(4),
+                Some(tree(&[
+                    ("Rap", &["Drill", "Grime"]),
+                    ("Reggae", &[]),
+                    ("Rock", &["Hardcore", "Trap"]),
+                ]))
+            );
+            assert_eq!(
+                MusicStylesPallet::styles_at_version(0),
+                Some(genesis.clone())
+            );
+
+            assert_ok!(MusicStylesPallet::revert_changes(
+                RuntimeOrigin::root(),
+                6,
+                None
+            ));
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+        });
+    }
+
+    #[test]
+    fn new_styles_are_not_rate_limited() {
+        new_test_ext(true).execute_with(|| {
+            MaxNewParentStylesPerPeriod::set(0);
+            MaxNewSubStylesPerPeriod::set(0);
+            let new = tree(&[("Jazz", &["Bebop", "Swing"]), ("Rap", &["Drill"])]);
+
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::root(),
+                new.clone(),
+                None
+            ));
+            assert_eq!(MusicStylesPallet::get_styles(), new);
+            assert_has_event(SubStyleAdded {
+                parent: bounded(b"Jazz"),
+                name: bounded(b"Swing"),
+                who: None,
+                version: MusicStylesPallet::taxonomy_version(),
+            });
+        });
+    }
+
+    #[test]
+    fn referenced_styles_are_not_removed() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Trap")));
+
+            assert_noop!(
+                MusicStylesPallet::set_taxonomy(
+                    RuntimeOrigin::root(),
+                    tree(&[("Rock", &["Hardcore", "Trap"])]),
+                    None
+                ),
+                Error::<Test>::StyleInUse
+            );
+
+            // A referenced sub style can still be moved
+            let new = tree(&[("Rap", &["Drill"]), ("Rock", &["Hardcore", "Trap"])]);
+            assert_ok!(MusicStylesPallet::set_taxonomy(
+                RuntimeOrigin::root(),
+                new.clone(),
+                None
+            ));
+            assert_eq!(MusicStylesPallet::get_styles(), new);
+        });
+    }
+
+    #[test]
+    fn invalid_taxonomies_are_rejected() {
+        new_test_ext(true).execute_with(|| {
+            let mut new = tree(&[("Rap", &["Drill", "Drill"])]);
+
+            assert_noop!(
+                MusicStylesPallet::set_taxonomy(RuntimeOrigin::signed(BOB), new.clone(), None),
+                BadOrigin
+            );
+            assert_noop!(
+                MusicStylesPallet::set_taxonomy(RuntimeOrigin::root(), new.clone(), None),
+                Error::<Test>::NameAlreadyExists
+            );
+
+            new = tree(&[("Rap", &["Drill"])]);
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), true));
+            assert_noop!(
+                MusicStylesPallet::set_taxonomy(RuntimeOrigin::root(), new.clone(), None),
+                Error::<Test>::StyleFrozen
+            );
+            assert_ok!(MusicStylesPallet::set_frozen(RuntimeOrigin::root(), false));
+
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+            assert_noop!(
+                MusicStylesPallet::set_taxonomy(RuntimeOrigin::root(), new, None),
+                Error::<Test>::PalletPaused
+            );
+        });
+    }
+}
+
+mod import {
+    use super::*;
+    use crate::traits::StyleReferences;
+    use frame_support::traits::Hooks;
+
+    fn chunks(chunks: &[&[(&str, &[&str])]]) -> ImportChunks {
+        BoundedVec::truncate_from(chunks.iter().map(|chunk| tree(chunk)).collect())
+    }
+
+    fn assert_import_cleared() {
+        assert_eq!(MusicStylesPallet::pending_import(), None);
+        assert_eq!(QueuedChunks::<Test>::iter().count(), 0);
+        assert_eq!(StagedStyles::<Test>::count(), 0);
+    }
+
+    #[test]
+    fn chunks_are_staged_across_blocks_then_swapped() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::signed(DAVE),
+                chunks(&[
+                    &[("Rap", &["Drill", "Grime"])],
+                    &[("Reggae", &[])],
+                    &[("Rock", &["Hardcore", "Trap"])],
+                ]),
+                None
+            ));
+            assert_last_event(ImportQueued {
+                chunks: 3,
+                who: Some(DAVE),
+            });
+
+            // Only the first chunk fits
+            MusicStylesPallet::on_idle(1, Weight::from_parts(3_000, 0));
+            assert_last_event(ImportProgress {
+                staged: 1,
+                total: 3,
+            });
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+
+            // The styles tree is only replaced when the swap fits as well
+            MusicStylesPallet::on_idle(2, Weight::from_parts(4_000, 0));
+            assert_last_event(ImportProgress {
+                staged: 3,
+                total: 3,
+            });
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_eq!(StagedStyles::<Test>::count(), 3);
+
+            MusicStylesPallet::on_idle(3, Weight::MAX);
+            assert_eq!(
+                MusicStylesPallet::get_styles(),
+                tree(&[
+                    ("Rap", &["Drill", "Grime"]),
+                    ("Reggae", &[]),
+                    ("Rock", &["Hardcore", "Trap"]),
+                ])
+            );
+            assert_has_event(SubStyleMoved {
+                parent: bounded(b"Rap"),
+                new_parent: bounded(b"Rock"),
+                name: bounded(b"Trap"),
+                who: Some(DAVE),
+                version: 3,
+            });
+            assert_last_event(ImportCompleted {
+                version: 4,
+                who: Some(DAVE),
+            });
+            assert_import_cleared();
+        });
+    }
+
+    #[test]
+    fn swap_waits_for_enough_weight_without_rate_limiting() {
+        new_test_ext(true).execute_with(|| {
+            MaxNewParentStylesPerPeriod::set(1);
+            MaxNewSubStylesPerPeriod::set(1);
+            let genesis = MusicStylesPallet::get_styles();
+            let new = tree(&[
+                ("Jazz", &["Bebop", "Swing"]),
+                ("Rap", &["Drill"]),
+                ("Reggae", &[]),
+            ]);
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::signed(DAVE),
+                chunks(&[
+                    &[("Jazz", &["Bebop", "Swing"])],
+                    &[("Rap", &["Drill"]), ("Reggae", &[])]
+                ]),
+                None
+            ));
+
+            // The chunks are staged, the swap doesn't fit anymore and the tree is left untouched
+            MusicStylesPallet::on_idle(1, Weight::from_parts(6_000, 0));
+            assert_eq!(StagedStyles::<Test>::count(), 3);
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_eq!(
+                MusicStylesPallet::pending_import().and_then(|import| import.swap_weight),
+                Some(Weight::from_parts(8_000, 0))
+            );
+
+            // The references are frozen until the swap
+            assert_noop!(
+                MusicStylesPallet::inc_ref(&bounded(b"Drill")),
+                Error::<Test>::ImportInProgress
+            );
+            assert_noop!(
+                MusicStylesPallet::dec_ref(&bounded(b"Drill")),
+                Error::<Test>::ImportInProgress
+            );
+
+            // Eight notifications are needed, the swap isn't done partly
+            MusicStylesPallet::on_idle(2, Weight::from_parts(7_000, 0));
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 0);
+
+            MusicStylesPallet::on_idle(3, Weight::MAX);
+            assert_eq!(MusicStylesPallet::get_styles(), new);
+            assert_has_event(SubStyleAdded {
+                parent: bounded(b"Jazz"),
+                name: bounded(b"Swing"),
+                who: Some(DAVE),
+                version: 7,
+            });
+            assert_last_event(ImportCompleted {
+                version: 7,
+                who: Some(DAVE),
+            });
+            assert_import_cleared();
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Drill")));
+        });
+    }
+
+    #[test]
+    fn parent_styles_can_be_spread_over_chunks() {
+        new_test_ext(false).execute_with(|| {
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::root(),
+                chunks(&[&[("Rap", &["Drill"])], &[("Rap", &["Trap"]), ("Rock", &[])]]),
+                None
+            ));
+            MusicStylesPallet::on_idle(1, Weight::MAX);
+
+            assert_eq!(
+                MusicStylesPallet::get_styles(),
+                tree(&[("Rap", &["Drill", "Trap"]), ("Rock", &[])])
+            );
+            assert_import_cleared();
+        });
+    }
+
+    #[test]
+    fn invalid_imports_are_dropped() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::root(),
+                chunks(&[&[("Rap", &["Drill"])], &[("Rap", &["Drill"])]]),
+                None
+            ));
+            MusicStylesPallet::on_idle(1, Weight::MAX);
+            assert_last_event(ImportFailed {
+                error: Error::<Test>::NameAlreadyExists.into(),
+            });
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_import_cleared();
+
+            // The swap fails as a whole
+            assert_ok!(MusicStylesPallet::inc_ref(&bounded(b"Hardcore")));
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::root(),
+                chunks(&[&[("Jazz", &["Bebop"])]]),
+                None
+            ));
+            MusicStylesPallet::on_idle(2, Weight::MAX);
+            assert_last_event(ImportFailed {
+                error: Error::<Test>::StyleInUse.into(),
+            });
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_eq!(MusicStylesPallet::taxonomy_version(), 0);
+            assert_import_cleared();
+        });
+    }
+
+    #[test]
+    fn imports_can_be_cancelled() {
+        new_test_ext(true).execute_with(|| {
+            let genesis = MusicStylesPallet::get_styles();
+            let import = chunks(&[&[("Jazz", &[])], &[("Rock", &[])]]);
+
+            assert_noop!(
+                MusicStylesPallet::queue_import(RuntimeOrigin::signed(BOB), import.clone(), None),
+                BadOrigin
+            );
+            assert_noop!(
+                MusicStylesPallet::cancel_import(RuntimeOrigin::signed(BOB)),
+                BadOrigin
+            );
+            assert_noop!(
+                MusicStylesPallet::queue_import(RuntimeOrigin::root(), chunks(&[]), None),
+                Error::<Test>::EmptyImport
+            );
+            assert_noop!(
+                MusicStylesPallet::cancel_import(RuntimeOrigin::root()),
+                Error::<Test>::NoImport
+            );
+
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::root(),
+                import.clone(),
+                None
+            ));
+            assert_noop!(
+                MusicStylesPallet::queue_import(RuntimeOrigin::root(), import, None),
+                Error::<Test>::ImportInProgress
+            );
+            MusicStylesPallet::on_idle(1, Weight::from_parts(1_000, 0));
+            assert_eq!(
+                MusicStylesPallet::pending_import().map(|import| import.staged),
+                Some(1)
+            );
+
+            assert_ok!(MusicStylesPallet::cancel_import(RuntimeOrigin::signed(
+                DAVE
+            )));
+            assert_last_event(ImportCancelled { who: Some(DAVE) });
+            MusicStylesPallet::on_idle(2, Weight::MAX);
+            assert_eq!(MusicStylesPallet::get_styles(), genesis);
+            assert_import_cleared();
+        });
+    }
+
+    #[test]
+    fn imports_wait_while_the_pallet_is_paused() {
+        new_test_ext(true).execute_with(|| {
+            assert_ok!(MusicStylesPallet::queue_import(
+                RuntimeOrigin::root(),
+                chunks(&[&[("Jazz", &[])]]),
+                None
+            ));
+            assert_ok!(MusicStylesPallet::pause(RuntimeOrigin::signed(CHARLIE)));
+            assert_noop!(
+                MusicStylesPallet::queue_import(
+                    RuntimeOrigin::root(),
+                    chunks(&[&[("Jazz", &[])]]),
+                    None
+                ),
+                Error::<Test>::PalletPaused
+            );
+            assert_noop!(
+                MusicStylesPallet::cancel_import(RuntimeOrigin::root()),
+                Error::<Test>::PalletPaused
+            );
+
+            MusicStylesPallet::on_idle(1, Weight::MAX);
+            assert_eq!(
+                MusicStylesPallet::pending_import().map(|import| import.staged),
+                Some(0)
+            );
+
+            assert_ok!(MusicStylesPallet::unpause(RuntimeOrigin::signed(CHARLIE)));
+            MusicStylesPallet::on_idle(2, Weight::MAX);
+            assert_eq!(MusicStylesPallet::get_styles(), tree(&[("Jazz", &[])]));
+            assert_import_cleared();
+        });
+    }
+}
\ No newline at end of file
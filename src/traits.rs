@@ -0,0 +1,86 @@
+use super::*;
+
+/// Inspect the music styles according to their lifecycle status
+pub trait InspectStyleStatus {
+    /// Status of an existing style, `None` if the style doesn't exist or is still a draft
+    fn exist_with_status(style_name: &MusicStyleName) -> Option<StyleStatus>;
+
+    /// Whether the style exists and can be used for new references
+    fn is_usable(style_name: &MusicStyleName) -> bool {
+        Self::exist_with_status(style_name).is_some_and(|status| status.is_usable())
+    }
+
+    /// Suggested replacement of a deprecated style
+    fn replacement(style_name: &MusicStyleName) -> Option<MusicStyleName>;
+}
+
+/// Inspect the version of the styles tree, so a copy of the tree can be checked against the
+/// on-chain one without downloading it again
+pub trait InspectTaxonomyVersion {
+    /// Version of the styles tree, bumped on every addition, rename or removal of a style
+    fn taxonomy_version() -> u64;
+
+    /// Blake2-256 hash of the SCALE encoded styles tree
+    fn taxonomy_hash() -> H256;
+}
+
+/// Weight of the `InspectMusicStyles` queries, so the pallets calling them can charge for it
+pub trait InspectMusicStylesWeight {
+    /// Weight of `parent_styles`
+    fn parent_styles_weight() -> Weight;
+
+    /// Weight of `sub_styles`
+    fn sub_styles_weight() -> Weight;
+
+    /// Weight of `is_parent_style`
+    fn is_parent_style_weight() -> Weight;
+
+    /// Weight of `is_sub_style`
+    fn is_sub_style_weight() -> Weight;
+
+    /// Weight of `exist`
+    fn exist_weight() -> Weight;
+}
+
+/// Handler notified of the changes of the styles tree, dependent pallets holding references to
+/// the styles can use it to keep them up to date
+pub trait StyleChangeHandler {
+    /// A style has been added
+    fn on_added(style: &StyleRef);
+
+    /// A style has been renamed (old, new)
+    fn on_renamed(old: &StyleRef, new: &StyleRef);
+
+    /// A style has been removed, the sub styles of a removed parent style are notified before it
+    fn on_removed(style: &StyleRef);
+
+    /// A sub style has been moved under another parent style (old, new)
+    fn on_moved(old: &StyleRef, new: &StyleRef);
+
+    /// Upper bound of the weight of a single notification, it is included in the weight of the
+    /// calls changing the styles tree
+    fn notification_weight() -> Weight;
+}
+
+impl StyleChangeHandler for () {
+    fn on_added(_style: &StyleRef) {}
+    fn on_renamed(_old: &StyleRef, _new: &StyleRef) {}
+    fn on_removed(_style: &StyleRef) {}
+    fn on_moved(_old: &StyleRef, _new: &StyleRef) {}
+    fn notification_weight() -> Weight {
+        Weight::zero()
+    }
+}
+
+/// Reference counting of the styles used by other pallets, a referenced style can't be removed
+/// unless the removal is forced
+pub trait StyleReferences {
+    /// Add a reference to an existing style, draft and retired styles can't be referenced
+    fn inc_ref(style_name: &MusicStyleName) -> DispatchResult;
+
+    /// Release a reference to a style
+    fn dec_ref(style_name: &MusicStyleName) -> DispatchResult;
+
+    /// Number of references to a style
+    fn references(style_name: &MusicStyleName) -> u32;
+}
\ No newline at end of file
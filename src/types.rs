@@ -0,0 +1,648 @@
+use super::*;
+
+/// Identifier of a scheduled style change
+pub type ChangeId = u32;
+
+/// Maximum length of the reason given for a change
+pub type MaxReasonLength = ConstU32<256>;
+
+/// Free text explaining why a change has been made
+pub type ChangeReason = BoundedVec<u8, MaxReasonLength>;
+
+/// Maximum number of chunks of a queued import
+pub type MaxImportChunks = ConstU32<64>;
+
+/// Chunks of a taxonomy imported across several blocks
+pub type ImportChunks = BoundedVec<MusicStyleDB, MaxImportChunks>;
+
+/// Reference to a style of the tree, sub styles are referenced through their parent style
+/// since the same sub style name could live under different parent styles.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum StyleRef {
+    /// A first level style
+    Parent(MusicStyleName),
+    /// A sub style (parent, sub)
+    Sub(MusicStyleName, MusicStyleName),
+}
+
+impl StyleRef {
+    /// Name of the referenced style
+    pub fn name(&self) -> &MusicStyleName {
+        match self {
+            StyleRef::Parent(name) => name,
+            StyleRef::Sub(_, name) => name,
+        }
+    }
+
+    /// First level style of the reference, which is the style itself for a parent style
+    pub fn parent(&self) -> &MusicStyleName {
+        match self {
+            StyleRef::Parent(name) => name,
+            StyleRef::Sub(parent, _) => parent,
+        }
+    }
+}
+
+/// A change of the styles tree, only the renames and removals can be scheduled
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum StyleChange {
+    /// Rename a style
+    Rename {
+        style: StyleRef,
+        new_name: MusicStyleName,
+    },
+    /// Remove a style, removing a parent style also removes its sub styles
+    /// A forced removal doesn't check whether the removed styles are still referenced
+    Remove { style: StyleRef, force: bool },
+    /// Add a parent style without sub styles, or a sub style after the existing ones
+    Add { style: StyleRef },
+    /// Move a sub style after the sub styles of another parent style
+    Move {
+        style: StyleRef,
+        new_parent: MusicStyleName,
+    },
+}
+
+impl StyleChange {
+    /// Style targeted by the change
+    pub fn style(&self) -> &StyleRef {
+        match self {
+            StyleChange::Rename { style, .. }
+            | StyleChange::Remove { style, .. }
+            | StyleChange::Add { style }
+            | StyleChange::Move { style, .. } => style,
+        }
+    }
+
+    /// Parent styles involved in the change: the parent style of the targeted style, along with
+    /// the new name of a renamed parent style or the new parent style of a moved sub style
+    pub fn parents(&self) -> Vec<&MusicStyleName> {
+        let mut parents = vec![self.style().parent()];
+        match self {
+            StyleChange::Rename {
+                style: StyleRef::Parent(_),
+                new_name,
+            } => parents.push(new_name),
+            StyleChange::Move { new_parent, .. } => parents.push(new_parent),
+            _ => {}
+        }
+        parents
+    }
+
+    /// The diff recording the change once applied to a styles tree, sub styles being added after
+    /// the existing ones. `None` if it can't be applied to the tree
+    pub fn to_diff(&self, tree: &MusicStyleDB) -> Option<TaxonomyDiff> {
+        let position = |parent: &MusicStyleName, name: &MusicStyleName| {
+            tree.get(parent)?
+                .iter()
+                .position(|sub| sub == name)
+                .map(|index| index as u32)
+        };
+
+        Some(match self.clone() {
+            StyleChange::Rename {
+                style: StyleRef::Parent(old_name),
+                new_name,
+            } => TaxonomyDiff::RenameParent { old_name, new_name },
+            StyleChange::Rename {
+                style: StyleRef::Sub(parent, old_name),
+                new_name,
+            } => TaxonomyDiff::RenameSub {
+                index: position(&parent, &old_name)?,
+                parent,
+                old_name,
+                new_name,
+            },
+            StyleChange::Remove {
+                style: StyleRef::Parent(name),
+                ..
+            } => TaxonomyDiff::RemoveParent {
+                subs: tree.get(&name)?.clone(),
+                name,
+            },
+            StyleChange::Remove {
+                style: StyleRef::Sub(parent, name),
+                ..
+            } => TaxonomyDiff::RemoveSub {
+                index: position(&parent, &name)?,
+                parent,
+                name,
+            },
+            StyleChange::Add {
+                style: StyleRef::Parent(name),
+            } => TaxonomyDiff::AddParent {
+                name,
+                subs: Default::default(),
+            },
+            StyleChange::Add {
+                style: StyleRef::Sub(parent, name),
+            } => TaxonomyDiff::AddSub {
+                index: tree.get(&parent)?.len() as u32,
+                parent,
+                name,
+            },
+            StyleChange::Move {
+                style: StyleRef::Parent(_),
+                ..
+            } => return None,
+            StyleChange::Move {
+                style: StyleRef::Sub(parent, name),
+                new_parent,
+            } => TaxonomyDiff::MoveSub {
+                index: position(&parent, &name)?,
+                new_index: tree.get(&new_parent)?.len() as u32,
+                name,
+                parent,
+                new_parent,
+            },
+        })
+    }
+
+    /// Apply the change to a styles tree, `None` if it can't be applied to the tree
+    pub fn apply(&self, tree: &mut MusicStyleDB) -> Option<()> {
+        match self {
+            StyleChange::Rename {
+                style: StyleRef::Parent(name),
+                new_name,
+            } => {
+                if tree.contains_key(new_name) {
+                    return None;
+                }
+                let subs = tree.remove(name)?;
+                tree.try_insert(new_name.clone(), subs).ok()?;
+            }
+            StyleChange::Rename {
+                style: StyleRef::Sub(parent, name),
+                new_name,
+            } => {
+                let subs = tree.get_mut(parent)?;
+                if subs.contains(new_name) {
+                    return None;
+                }
+                let index = subs.iter().position(|sub| sub == name)?;
+                subs[index] = new_name.clone();
+            }
+            StyleChange::Remove {
+                style: StyleRef::Parent(name),
+                ..
+            } => {
+                tree.remove(name)?;
+            }
+            StyleChange::Remove {
+                style: StyleRef::Sub(parent, name),
+                ..
+            } => {
+                let subs = tree.get_mut(parent)?;
+                let index = subs.iter().position(|sub| sub == name)?;
+                subs.remove(index);
+            }
+            StyleChange::Add {
+                style: StyleRef::Parent(name),
+            } => {
+                if tree.contains_key(name) {
+                    return None;
+                }
+                tree.try_insert(name.clone(), Default::default()).ok()?;
+            }
+            StyleChange::Add {
+                style: StyleRef::Sub(parent, name),
+            } => {
+                let subs = tree.get_mut(parent)?;
+                if subs.contains(name) {
+                    return None;
+                }
+                subs.try_push(name.clone()).ok()?;
+            }
+            StyleChange::Move {
+                style: StyleRef::Parent(_),
+                ..
+            } => return None,
+            StyleChange::Move {
+                style: StyleRef::Sub(parent, name),
+                new_parent,
+            } => {
+                let new_subs = tree.get(new_parent)?;
+                if parent == new_parent || new_subs.contains(name) {
+                    return None;
+                }
+                let subs = tree.get_mut(parent)?;
+                let index = subs.iter().position(|sub| sub == name)?;
+                subs.remove(index);
+                tree.get_mut(new_parent)?.try_push(name.clone()).ok()?;
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// A style change waiting for its enactment
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ScheduledChange<AccountId, BlockNumber> {
+    /// The change to apply
+    pub change: StyleChange,
+    /// Block at which the change will be enacted
+    pub when: BlockNumber,
+    /// Account which scheduled the change, if the admin origin was signed
+    pub who: Option<AccountId>,
+    /// Why the change has been scheduled
+    pub reason: Option<ChangeReason>,
+}
+
+/// An import waiting to be staged and swapped in, chunk after chunk
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PendingImport<AccountId> {
+    /// Number of parent and sub styles of each chunk, to weigh them before they are read
+    pub sizes: BoundedVec<u32, MaxImportChunks>,
+    /// Number of chunks already staged
+    pub staged: u32,
+    /// Weight of the swap once its diffs are known, it waits for a block with this much idle
+    /// weight
+    pub swap_weight: Option<Weight>,
+    /// Account which queued the import, if the admin origin was signed
+    pub who: Option<AccountId>,
+    /// Why the taxonomy is imported
+    pub reason: Option<ChangeReason>,
+}
+
+impl<AccountId> PendingImport<AccountId> {
+    /// Number of chunks of the import
+    pub fn total(&self) -> u32 {
+        self.sizes.len() as u32
+    }
+
+    /// Number of parent and sub styles of the whole import, some parent styles can be spread
+    /// over several chunks
+    pub fn styles(&self) -> u32 {
+        self.sizes
+            .iter()
+            .fold(0, |total, size| total.saturating_add(*size))
+    }
+}
+
+/// A change of the styles recorded in the change log
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum LogOperation {
+    /// A style has been added to the tree
+    Added(StyleRef),
+    /// A style has been renamed
+    Renamed {
+        style: StyleRef,
+        new_name: MusicStyleName,
+    },
+    /// A style has been removed from the tree, along with its sub styles for a parent style
+    Removed(StyleRef),
+    /// The lifecycle status of a name has been updated, wherever it appears in the tree
+    StatusUpdated {
+        name: MusicStyleName,
+        status: StyleStatus,
+    },
+    /// A sub style has been moved under another parent style
+    Moved {
+        style: StyleRef,
+        new_parent: MusicStyleName,
+    },
+    /// The sub styles of a parent style have been reordered
+    Reordered(MusicStyleName),
+}
+
+/// An entry of the change log
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ChangeLogEntry<AccountId, BlockNumber> {
+    /// The change and the style it targets
+    pub operation: LogOperation,
+    /// Account behind the change, if the admin origin was signed
+    pub who: Option<AccountId>,
+    /// Block at which the change has been applied
+    pub block: BlockNumber,
+    /// Why the change has been made
+    pub reason: Option<ChangeReason>,
+}
+
+/// Lifecycle status of a style
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum StyleStatus {
+    /// The style is being prepared and only visible through admin queries
+    Draft,
+    /// The style can be used for new references
+    #[default]
+    Active,
+    /// The style still resolves but should not be used anymore, a replacement can be suggested
+    Deprecated(Option<MusicStyleName>),
+    /// The style is kept for historical references but rejected for new use
+    Retired,
+}
+
+impl StyleStatus {
+    /// Whether the style is visible outside of admin queries
+    pub fn is_visible(&self) -> bool {
+        !matches!(self, StyleStatus::Draft)
+    }
+
+    /// Whether the style can be used for new references
+    pub fn is_usable(&self) -> bool {
+        matches!(self, StyleStatus::Active)
+    }
+}
+
+/// A primitive change of the styles tree, one is recorded for every taxonomy version. Sub styles
+/// are located by their position so a diff can be inverted exactly
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum TaxonomyDiff {
+    /// A parent style has been added along with its sub styles
+    AddParent {
+        name: MusicStyleName,
+        subs: MusicSubStyles,
+    },
+    /// A sub style has been inserted at the given position of its parent style
+    AddSub {
+        parent: MusicStyleName,
+        name: MusicStyleName,
+        index: u32,
+    },
+    /// A parent style has been renamed
+    RenameParent {
+        old_name: MusicStyleName,
+        new_name: MusicStyleName,
+    },
+    /// The sub style at the given position of its parent style has been renamed
+    RenameSub {
+        parent: MusicStyleName,
+        index: u32,
+        old_name: MusicStyleName,
+        new_name: MusicStyleName,
+    },
+    /// A parent style has been removed along with its sub styles
+    RemoveParent {
+        name: MusicStyleName,
+        subs: MusicSubStyles,
+    },
+    /// The sub style at the given position of its parent style has been removed
+    RemoveSub {
+        parent: MusicStyleName,
+        index: u32,
+        name: MusicStyleName,
+    },
+    /// A sub style has been moved from a position of its parent style to a position of another
+    /// parent style
+    MoveSub {
+        name: MusicStyleName,
+        parent: MusicStyleName,
+        index: u32,
+        new_parent: MusicStyleName,
+        new_index: u32,
+    },
+    /// The sub styles of a parent style have been reordered
+    ReorderSubs {
+        parent: MusicStyleName,
+        old_subs: MusicSubStyles,
+        new_subs: MusicSubStyles,
+    },
+}
+
+impl TaxonomyDiff {
+    /// The diff reverting this one
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            TaxonomyDiff::AddParent { name, subs } => TaxonomyDiff::RemoveParent { name, subs },
+            TaxonomyDiff::AddSub {
+                parent,
+                name,
+                index,
+            } => TaxonomyDiff::RemoveSub {
+                parent,
+                index,
+                name,
+            },
+            TaxonomyDiff::RenameParent { old_name, new_name } => TaxonomyDiff::RenameParent {
+                old_name: new_name,
+                new_name: old_name,
+            },
+            TaxonomyDiff::RenameSub {
+                parent,
+                index,
+                old_name,
+                new_name,
+            } => TaxonomyDiff::RenameSub {
+                parent,
+                index,
+                old_name: new_name,
+                new_name: old_name,
+            },
+            TaxonomyDiff::RemoveParent { name, subs } => TaxonomyDiff::AddParent { name, subs },
+            TaxonomyDiff::RemoveSub {
+                parent,
+                index,
+                name,
+            } => TaxonomyDiff::AddSub {
+                parent,
+                name,
+                index,
+            },
+            TaxonomyDiff::MoveSub {
+                name,
+                parent,
+                index,
+                new_parent,
+                new_index,
+            } => TaxonomyDiff::MoveSub {
+                name,
+                parent: new_parent,
+                index: new_index,
+                new_parent: parent,
+                new_index: index,
+            },
+            TaxonomyDiff::ReorderSubs {
+                parent,
+                old_subs,
+                new_subs,
+            } => TaxonomyDiff::ReorderSubs {
+                parent,
+                old_subs: new_subs,
+                new_subs: old_subs,
+            },
+        }
+    }
+
+    /// Parent styles changed by the diff
+    pub fn parents(&self) -> Vec<&MusicStyleName> {
+        match self {
+            TaxonomyDiff::AddParent { name, .. } | TaxonomyDiff::RemoveParent { name, .. } => {
+                vec![name]
+            }
+            TaxonomyDiff::AddSub { parent, .. }
+            | TaxonomyDiff::RenameSub { parent, .. }
+            | TaxonomyDiff::RemoveSub { parent, .. }
+            | TaxonomyDiff::ReorderSubs { parent, .. } => vec![parent],
+            TaxonomyDiff::RenameParent { old_name, new_name } => vec![old_name, new_name],
+            TaxonomyDiff::MoveSub {
+                parent, new_parent, ..
+            } => vec![parent, new_parent],
+        }
+    }
+
+    /// Number of changes notified to the dependent pallets for the diff
+    pub fn notifications(&self) -> u32 {
+        match self {
+            TaxonomyDiff::RemoveParent { subs, .. } => subs.len() as u32 + 1,
+            TaxonomyDiff::ReorderSubs { .. } => 0,
+            _ => 1,
+        }
+    }
+
+    /// Apply the diff to a styles tree, `None` if the tree doesn't match the diff
+    pub fn apply(&self, tree: &mut MusicStyleDB) -> Option<()> {
+        match self {
+            TaxonomyDiff::AddParent { name, subs } => {
+                if tree.contains_key(name) {
+                    return None;
+                }
+                tree.try_insert(name.clone(), subs.clone()).ok()?;
+            }
+            TaxonomyDiff::AddSub {
+                parent,
+                name,
+                index,
+            } => {
+                let subs = tree.get_mut(parent)?;
+                if subs.contains(name) || *index as usize > subs.len() {
+                    return None;
+                }
+                subs.try_insert(*index as usize, name.clone()).ok()?;
+            }
+            TaxonomyDiff::RenameParent { old_name, new_name } => {
+                if tree.contains_key(new_name) {
+                    return None;
+                }
+                let subs = tree.remove(old_name)?;
+                tree.try_insert(new_name.clone(), subs).ok()?;
+            }
+            TaxonomyDiff::RenameSub {
+                parent,
+                index,
+                old_name,
+                new_name,
+            } => {
+                let sub = tree.get_mut(parent)?.get_mut(*index as usize)?;
+                if sub != old_name {
+                    return None;
+                }
+                *sub = new_name.clone();
+            }
+            TaxonomyDiff::RemoveParent { name, subs } => {
+                if tree.get(name)? != subs {
+                    return None;
+                }
+                tree.remove(name);
+            }
+            TaxonomyDiff::RemoveSub {
+                parent,
+                index,
+                name,
+            } => {
+                let subs = tree.get_mut(parent)?;
+                if subs.get(*index as usize)? != name {
+                    return None;
+                }
+                subs.remove(*index as usize);
+            }
+            TaxonomyDiff::MoveSub {
+                name,
+                parent,
+                index,
+                new_parent,
+                new_index,
+            } => {
+                let new_subs = tree.get(new_parent)?;
+                if parent == new_parent
+                    || new_subs.contains(name)
+                    || *new_index as usize > new_subs.len()
+                {
+                    return None;
+                }
+                let subs = tree.get_mut(parent)?;
+                if subs.get(*index as usize)? != name {
+                    return None;
+                }
+                subs.remove(*index as usize);
+                tree.get_mut(new_parent)?
+                    .try_insert(*new_index as usize, name.clone())
+                    .ok()?;
+            }
+            TaxonomyDiff::ReorderSubs {
+                parent,
+                old_subs,
+                new_subs,
+            } => {
+                let subs = tree.get_mut(parent)?;
+                if subs != old_subs {
+                    return None;
+                }
+                *subs = new_subs.clone();
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// Why the genesis styles were rejected, along with the offending names
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum GenesisError {
+    /// The name is longer than `MaxNameLength`
+    NameTooLong(Vec<u8>),
+    /// The parent style is listed twice
+    DuplicateStyle(Vec<u8>),
+    /// The sub style is listed twice under its parent style (parent, sub)
+    DuplicateSubStyle(Vec<u8>, Vec<u8>),
+    /// The parent style holds more than `MaxSubStyles` sub styles
+    TooManySubStyles(Vec<u8>),
+    /// More than `MaxParentStyles` parent styles are listed, starting with this one
+    TooManyStyles(Vec<u8>),
+    /// The style is deprecated in favor of itself or of an unexisting style
+    InvalidReplacement(Vec<u8>),
+    /// The alias is already used by a style or by another alias
+    AliasConflict(Vec<u8>),
+}
+
+/// A name as text, when it is valid UTF-8
+fn display_name(name: &[u8]) -> &str {
+    sp_std::str::from_utf8(name).unwrap_or("<invalid UTF-8 name>")
+}
+
+impl sp_std::fmt::Display for GenesisError {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        match self {
+            GenesisError::NameTooLong(style) => {
+                write!(f, "the name of {} is too long", display_name(style))
+            }
+            GenesisError::DuplicateStyle(style) => {
+                write!(f, "{} is listed twice", display_name(style))
+            }
+            GenesisError::DuplicateSubStyle(parent, sub) => {
+                write!(
+                    f,
+                    "{} is listed twice under {}",
+                    display_name(sub),
+                    display_name(parent)
+                )
+            }
+            GenesisError::TooManySubStyles(parent) => {
+                write!(f, "{} holds too many sub styles", display_name(parent))
+            }
+            GenesisError::TooManyStyles(style) => {
+                write!(
+                    f,
+                    "too many parent styles, {} is over the limit",
+                    display_name(style)
+                )
+            }
+            GenesisError::InvalidReplacement(style) => {
+                write!(f, "{} has an invalid replacement", display_name(style))
+            }
+            GenesisError::AliasConflict(alias) => {
+                write!(f, "the alias {} is already used", display_name(alias))
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -33,19 +33,229 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
     fn add_style(n: u32, x: u32, ) -> Weight;
     fn add_sub_style(n: u32, x: u32, ) -> Weight;
+    fn update_style_name() -> Weight;
+    fn remove_style() -> Weight;
+    fn cancel_change() -> Weight;
+    fn enact_changes(n: u32, ) -> Weight;
+    fn defer_changes(n: u32, ) -> Weight;
+    fn refresh_taxonomy_hash(p: u32, ) -> Weight;
+    fn set_frozen() -> Weight;
+    fn set_style_lock() -> Weight;
+    fn pause() -> Weight;
+    fn unpause() -> Weight;
+    fn set_style_status() -> Weight;
+    fn revert_changes(n: u32, ) -> Weight;
+    fn set_taxonomy(n: u32, p: u32, ) -> Weight;
+    fn queue_import(c: u32, n: u32, ) -> Weight;
+    fn stage_import(n: u32, ) -> Weight;
+    fn cancel_import(c: u32, p: u32, ) -> Weight;
+    fn swap_import(n: u32, p: u32, ) -> Weight;
+    fn apply_import_diff() -> Weight;
+    fn resume_styles_migration() -> Weight;
+    fn parent_styles(p: u32, ) -> Weight;
+    fn sub_styles(p: u32, ) -> Weight;
+    fn is_parent_style() -> Weight;
+    fn is_sub_style(p: u32, ) -> Weight;
+    fn exist(p: u32, ) -> Weight;
 }
 
 impl WeightInfo for () {
-    // Storage: MusicStyles Styles (r:1 w:1)
+    // Storage: MusicStyles ParentStyles (r:1 w:1)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:1)
+    // Storage: MusicStyles SubStyleIndex (r:0 w:50)
+    // Storage: MusicStyles NewParentStyles (r:1 w:1)
+    // Storage: MusicStyles NewSubStyles (r:1 w:1)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:51)
     /// The range of component `n` is `[1, 64]`.
     /// The range of component `x` is `[0, 50]`.
     fn add_style(n: u32, x: u32, ) -> Weight {
         Weight::default()
     }
-    // Storage: MusicStyles Styles (r:1 w:1)
+    // Storage: MusicStyles ParentStyles (r:1 w:1)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:1)
+    // Storage: MusicStyles SubStyleIndex (r:0 w:50)
+    // Storage: MusicStyles NewParentStyles (r:1 w:1)
+    // Storage: MusicStyles NewSubStyles (r:1 w:1)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:50)
     /// The range of component `n` is `[1, 64]`.
     /// The range of component `x` is `[1, 50]`.
     fn add_sub_style(n: u32, x: u32, ) -> Weight {
         Weight::default()
     }
-}
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles NextChangeId (r:1 w:1)
+    // Storage: MusicStyles Agenda (r:1 w:1)
+    // Storage: MusicStyles ScheduledChanges (r:0 w:1)
+    fn update_style_name() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles NextChangeId (r:1 w:1)
+    // Storage: MusicStyles Agenda (r:1 w:1)
+    // Storage: MusicStyles ScheduledChanges (r:0 w:1)
+    fn remove_style() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ScheduledChanges (r:1 w:1)
+    // Storage: MusicStyles Agenda (r:1 w:1)
+    fn cancel_change() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Agenda (r:1 w:1)
+    // Storage: MusicStyles ScheduledChanges (r:1 w:1)
+    // Storage: MusicStyles ParentStyles (r:1 w:1)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:1)
+    // Storage: MusicStyles SubStyleIndex (r:0 w:50)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:2)
+    /// The range of component `n` is `[0, 50]`.
+    fn enact_changes(n: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Agenda (r:1 w:1)
+    // Storage: MusicStyles DeferredRange (r:1 w:1)
+    // Storage: MusicStyles DeferredChanges (r:0 w:50)
+    /// The range of component `n` is `[0, 50]`.
+    fn defer_changes(n: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles TaxonomyHash (r:1 w:1)
+    // Storage: MusicStyles StylesMigration (r:1 w:0)
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    fn refresh_taxonomy_hash(p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Frozen (r:0 w:1)
+    fn set_frozen() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles LockedStyles (r:0 w:1)
+    fn set_style_lock() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Paused (r:0 w:1)
+    fn pause() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Paused (r:0 w:1)
+    fn unpause() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Paused (r:1 w:0)
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles StyleStatuses (r:0 w:1)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:2)
+    fn set_style_status() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Paused (r:1 w:0)
+    // Storage: MusicStyles Frozen (r:1 w:0)
+    // Storage: MusicStyles TaxonomyVersion (r:1 w:1)
+    // Storage: MusicStyles History (r:50 w:100)
+    // Storage: MusicStyles ParentStyles (r:1 w:50)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:0)
+    // Storage: MusicStyles SubStyleIndex (r:0 w:50)
+    // Storage: MusicStyles StyleStatuses (r:0 w:50)
+    // Storage: MusicStyles StyleReferenceCount (r:50 w:50)
+    // Storage: MusicStyles LockedStyles (r:50 w:0)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:100)
+    // Storage: MusicStyles TaxonomyHash (r:0 w:1)
+    /// The range of component `n` is `[1, 50]`.
+    fn revert_changes(n: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Paused (r:1 w:0)
+    // Storage: MusicStyles Frozen (r:1 w:0)
+    // Storage: MusicStyles ParentStyles (r:101 w:101)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:1)
+    // Storage: MusicStyles SubStyleIndex (r:100 w:100)
+    // Storage: MusicStyles StyleReferenceCount (r:50 w:50)
+    // Storage: MusicStyles LockedStyles (r:51 w:0)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:51)
+    // Storage: MusicStyles TaxonomyHash (r:0 w:1)
+    /// The range of component `n` is `[1, 51]`.
+    fn set_taxonomy(n: u32, p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Paused (r:1 w:0)
+    // Storage: MusicStyles Import (r:1 w:1)
+    // Storage: MusicStyles QueuedChunks (r:0 w:64)
+    /// The range of component `c` is `[1, 64]`.
+    /// The range of component `n` is `[1, 51]`.
+    fn queue_import(c: u32, n: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles QueuedChunks (r:1 w:1)
+    // Storage: MusicStyles StagedStyles (r:1 w:1)
+    // Storage: MusicStyles CounterForStagedStyles (r:1 w:1)
+    /// The range of component `n` is `[1, 49]`.
+    fn stage_import(n: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Import (r:1 w:1)
+    // Storage: MusicStyles QueuedChunks (r:0 w:64)
+    // Storage: MusicStyles StagedStyles (r:0 w:1)
+    // Storage: MusicStyles CounterForStagedStyles (r:0 w:1)
+    /// The range of component `c` is `[0, 63]`.
+    fn cancel_import(c: u32, p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles Import (r:1 w:0)
+    // Storage: MusicStyles ParentStyles (r:101 w:0)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:0)
+    // Storage: MusicStyles StagedStyles (r:1 w:0)
+    // Storage: MusicStyles StyleReferenceCount (r:50 w:0)
+    // Storage: MusicStyles LockedStyles (r:51 w:0)
+    /// The range of component `n` is `[1, 51]`.
+    fn swap_import(n: u32, p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:1)
+    // Storage: MusicStyles CounterForParentStyles (r:1 w:1)
+    // Storage: MusicStyles SubStyleIndex (r:50 w:50)
+    // Storage: MusicStyles StyleStatuses (r:0 w:51)
+    // Storage: MusicStyles StyleReferenceCount (r:0 w:51)
+    // Storage: MusicStyles TaxonomyVersion (r:1 w:1)
+    // Storage: MusicStyles History (r:0 w:2)
+    // Storage: MusicStyles NextLogIndex (r:1 w:1)
+    // Storage: MusicStyles ChangeLog (r:0 w:2)
+    fn apply_import_diff() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles StylesMigration (r:1 w:1)
+    fn resume_styles_migration() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles StyleStatuses (r:1 w:0)
+    fn parent_styles(p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles StyleStatuses (r:1 w:0)
+    fn sub_styles(p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles StyleStatuses (r:1 w:0)
+    fn is_parent_style() -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles SubStyleIndex (r:1 w:0)
+    // Storage: MusicStyles StyleStatuses (r:1 w:0)
+    fn is_sub_style(p: u32, ) -> Weight {
+        Weight::default()
+    }
+    // Storage: MusicStyles ParentStyles (r:1 w:0)
+    // Storage: MusicStyles SubStyleIndex (r:1 w:0)
+    // Storage: MusicStyles StyleStatuses (r:1 w:0)
+    fn exist(p: u32, ) -> Weight {
+        Weight::default()
+    }
+}
\ No newline at end of file
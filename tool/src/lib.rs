@@ -0,0 +1,308 @@
+//! Offline tooling for the music styles taxonomies: validate a JSON or CSV taxonomy, compare it to
+//! a SCALE encoded dump of the on-chain styles with [`diff`] and build the calls applying it
+//!
+//! Nothing here talks to a node, the calls are returned encoded so they can be submitted through
+//! governance.
+
+#[cfg(test)]
+mod tests;
+
+pub mod runtime;
+
+use allfeat_support::types::music::style::{MusicStyleDB, MusicStyleName};
+use codec::{DecodeAll, Encode};
+use pallet_music_styles::{io::IoError, ChangeReason, StyleChange, StyleRef};
+use runtime::Runtime;
+use std::{fmt, path::Path};
+
+pub use pallet_music_styles::diff;
+
+pub type Call = pallet_music_styles::Call<Runtime>;
+
+/// Why the tool couldn't complete
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read
+    File(std::io::Error),
+    /// The taxonomy file is invalid
+    Taxonomy(IoError),
+    /// The styles dump isn't a SCALE encoded styles tree
+    State(String),
+    /// The reason is longer than `MaxReasonLength`
+    ReasonTooLong,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::File(error) => write!(f, "can't read the file: {}", error),
+            Error::Taxonomy(error) => write!(f, "invalid taxonomy, {}", error),
+            Error::State(error) => write!(f, "invalid styles dump: {}", error),
+            Error::ReasonTooLong => f.write_str("the reason is too long"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Format of a taxonomy file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    /// Format matching the extension of a file, JSON unless it ends with `.csv`
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Format::Csv,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// Read and validate a taxonomy
+pub fn parse_taxonomy(input: &str, format: Format) -> Result<MusicStyleDB, Error> {
+    match format {
+        Format::Json => pallet_music_styles::io::from_json::<Runtime>(input),
+        Format::Csv => pallet_music_styles::io::from_csv::<Runtime>(input),
+    }
+    .map_err(Error::Taxonomy)
+}
+
+/// Read and validate a taxonomy file, in the given format or the one of its extension
+pub fn read_taxonomy(path: &Path, format: Option<Format>) -> Result<MusicStyleDB, Error> {
+    let input = std::fs::read_to_string(path).map_err(Error::File)?;
+    parse_taxonomy(&input, format.unwrap_or_else(|| Format::of(path)))
+}
+
+/// Decode a styles dump, given as raw SCALE bytes or as `0x` prefixed hex like the node returns
+pub fn decode_state(dump: &[u8]) -> Result<MusicStyleDB, Error> {
+    let bytes = match std::str::from_utf8(dump).map(str::trim) {
+        Ok(hex) if hex.starts_with("0x") => {
+            sp_core::bytes::from_hex(hex).map_err(|error| Error::State(error.to_string()))?
+        }
+        _ => dump.to_vec(),
+    };
+
+    MusicStyleDB::decode_all(&mut &bytes[..]).map_err(|error| Error::State(error.to_string()))
+}
+
+/// Read a styles dump file
+pub fn read_state(path: &Path) -> Result<MusicStyleDB, Error> {
+    decode_state(&std::fs::read(path).map_err(Error::File)?)
+}
+
+fn name(name: &MusicStyleName) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+fn style(style: &StyleRef) -> String {
+    match style {
+        StyleRef::Parent(parent) => name(parent),
+        StyleRef::Sub(parent, sub) => format!("{} / {}", name(parent), name(sub)),
+    }
+}
+
+/// One line description of a change, `+` for the additions, `-` for the removals, `~` for the
+/// renames and `>` for the moves
+pub fn describe(change: &StyleChange) -> String {
+    match change {
+        StyleChange::Add { style: added } => format!("+ {}", style(added)),
+        StyleChange::Remove { style: removed, .. } => format!("- {}", style(removed)),
+        StyleChange::Rename {
+            style: renamed,
+            new_name,
+        } => format!("~ {} -> {}", style(renamed), name(new_name)),
+        StyleChange::Move {
+            style: moved,
+            new_parent,
+        } => format!("> {} -> {}", style(moved), name(new_parent)),
+    }
+}
+
+/// Bound a reason given on the command line
+pub fn to_reason(reason: Option<String>) -> Result<Option<ChangeReason>, Error> {
+    reason
+        .map(|reason| ChangeReason::try_from(reason.into_bytes()))
+        .transpose()
+        .map_err(|_| Error::ReasonTooLong)
+}
+
+/// Pallet calls applying the changes of [`diff`], in their dispatch order
+///
+/// Renames and removals are scheduled by the pallet and enacted together `EnactmentDelay` blocks
+/// later, so every call names the styles as they are before the changes: the additions come
+/// first, the sub styles being added beneath the current name of their parent style, then the
+/// scheduled changes, the renames of the parent styles last. The pallet has no call moving a sub
+/// style, a move is the addition of the sub style beneath its new parent style followed by its
+/// removal from the old one.
+pub fn to_calls(changes: &[StyleChange], reason: Option<ChangeReason>) -> Vec<Call> {
+    // Current name of the parent styles renamed by the changes
+    let current = |parent: &MusicStyleName| -> MusicStyleName {
+        changes
+            .iter()
+            .find_map(|change| match change {
+                StyleChange::Rename {
+                    style: StyleRef::Parent(old_name),
+                    new_name,
+                } if new_name == parent => Some(old_name.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| parent.clone())
+    };
+
+    // Sub styles added beneath each parent style, in the order of the changes
+    let mut added: Vec<(MusicStyleName, Vec<Vec<u8>>)> = Vec::new();
+    let mut add = |parent: MusicStyleName, sub: Option<&MusicStyleName>| {
+        let index = match added.iter().position(|(name, _)| *name == parent) {
+            Some(index) => index,
+            None => {
+                added.push((parent, Vec::new()));
+                added.len() - 1
+            }
+        };
+        added[index].1.extend(sub.map(|sub| sub.to_vec()));
+    };
+    let mut added_parents = Vec::new();
+    let mut scheduled = Vec::new();
+    let mut parent_changes = Vec::new();
+
+    for change in changes {
+        match change {
+            StyleChange::Add {
+                style: StyleRef::Parent(name),
+            } => {
+                added_parents.push(name.clone());
+                add(name.clone(), None);
+            }
+            StyleChange::Add {
+                style: StyleRef::Sub(parent, sub),
+            } => add(current(parent), Some(sub)),
+            StyleChange::Move {
+                style: StyleRef::Sub(parent, sub),
+                new_parent,
+            } => {
+                add(current(new_parent), Some(sub));
+                scheduled.push(Call::remove_style {
+                    parent_style: current(parent).into_inner(),
+                    sub_style: Some(sub.to_vec()),
+                    reason: reason.clone(),
+                });
+            }
+            StyleChange::Move {
+                style: StyleRef::Parent(_),
+                ..
+            } => {}
+            StyleChange::Remove {
+                style: StyleRef::Sub(parent, sub),
+                ..
+            } => scheduled.push(Call::remove_style {
+                parent_style: current(parent).into_inner(),
+                sub_style: Some(sub.to_vec()),
+                reason: reason.clone(),
+            }),
+            StyleChange::Rename {
+                style: StyleRef::Sub(parent, sub),
+                new_name,
+            } => scheduled.push(Call::update_style_name {
+                parent_style: current(parent).into_inner(),
+                sub_style: Some(sub.to_vec()),
+                new_name: new_name.to_vec(),
+                reason: reason.clone(),
+            }),
+            StyleChange::Remove {
+                style: StyleRef::Parent(name),
+                ..
+            } => scheduled.push(Call::remove_style {
+                parent_style: name.to_vec(),
+                sub_style: None,
+                reason: reason.clone(),
+            }),
+            StyleChange::Rename {
+                style: StyleRef::Parent(name),
+                new_name,
+            } => parent_changes.push(Call::update_style_name {
+                parent_style: name.to_vec(),
+                sub_style: None,
+                new_name: new_name.to_vec(),
+                reason: reason.clone(),
+            }),
+        }
+    }
+
+    let mut calls: Vec<Call> = added
+        .into_iter()
+        .map(|(parent, subs)| {
+            if added_parents.contains(&parent) {
+                Call::add_style {
+                    name: parent.into_inner(),
+                    sub: (!subs.is_empty()).then_some(subs),
+                    reason: reason.clone(),
+                }
+            } else {
+                Call::add_sub_style {
+                    parent_style: parent.into_inner(),
+                    subs_style: subs,
+                    reason: reason.clone(),
+                }
+            }
+        })
+        .collect();
+    calls.extend(scheduled);
+    calls.extend(parent_changes);
+    calls
+}
+
+/// Whether the pallet schedules the change made by the call in the agenda of a later block
+pub fn is_scheduled(call: &Call) -> bool {
+    matches!(
+        call,
+        Call::update_style_name { .. }
+            | Call::remove_style { .. }
+            | Call::force_remove_style { .. }
+    )
+}
+
+/// Split the calls of [`to_calls`] into batches holding at most `max_scheduled` scheduled calls,
+/// the runtime `MaxScheduledPerBlock`, so each batch fits in the agenda of its enactment block
+/// when the batches are submitted in distinct blocks. The order of the calls is kept
+pub fn to_batches(calls: Vec<Call>, max_scheduled: u32) -> Vec<Vec<Call>> {
+    let mut batches = vec![Vec::new()];
+    let mut scheduled = 0;
+
+    for call in calls {
+        if is_scheduled(&call) {
+            if scheduled == max_scheduled.max(1) {
+                batches.push(Vec::new());
+                scheduled = 0;
+            }
+            scheduled += 1;
+        }
+        batches
+            .last_mut()
+            .expect("there is always a batch")
+            .push(call);
+    }
+
+    batches
+}
+
+/// The `set_taxonomy` call replacing the whole styles tree by the taxonomy
+pub fn replace_call(taxonomy: MusicStyleDB, reason: Option<ChangeReason>) -> Call {
+    Call::set_taxonomy {
+        new: taxonomy,
+        reason,
+    }
+}
+
+/// Call data of a pallet call, for the runtime holding the pallet at `pallet_index`
+pub fn encode_call(pallet_index: u8, call: &Call) -> Vec<u8> {
+    (pallet_index, call).encode()
+}
+
+/// Call data as `0x` prefixed hex
+pub fn to_hex(call_data: &[u8]) -> String {
+    sp_core::bytes::to_hex(call_data, false)
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+//! Validate music styles taxonomies and generate the calls applying them, fully offline
+
+use allfeat_support::types::music::style::MusicStyleDB;
+use clap::{Parser, Subcommand};
+use pallet_music_styles_tool::{
+    describe, diff, encode_call, is_scheduled, read_state, read_taxonomy, replace_call, to_batches,
+    to_calls, to_hex, to_reason, Error, Format,
+};
+use std::{path::PathBuf, process::ExitCode};
+
+#[derive(Parser)]
+#[command(name = "music-styles-tool", about, version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a taxonomy file against the bounds and name rules of the pallet
+    Validate {
+        /// JSON or CSV taxonomy
+        file: PathBuf,
+        /// Format of the file, guessed from its extension when not given
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Print the changes turning the current styles into a taxonomy
+    Diff {
+        /// JSON or CSV taxonomy
+        file: PathBuf,
+        /// SCALE encoded styles tree, raw or as `0x` prefixed hex
+        #[arg(long)]
+        state: PathBuf,
+        /// Format of the file, guessed from its extension when not given
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Print the hex encoded calls turning the current styles into a taxonomy, one per line
+    Calls {
+        /// JSON or CSV taxonomy
+        file: PathBuf,
+        /// SCALE encoded styles tree, raw or as `0x` prefixed hex, no styles when not given
+        #[arg(long)]
+        state: Option<PathBuf>,
+        /// Index of the music styles pallet in the runtime
+        #[arg(long)]
+        pallet_index: u8,
+        /// Reason recorded along with every change
+        #[arg(long)]
+        reason: Option<String>,
+        /// Print a single `set_taxonomy` call replacing the whole styles tree
+        #[arg(long, conflicts_with = "state")]
+        replace: bool,
+        /// `MaxScheduledPerBlock` of the runtime, the calls are printed in batches separated by
+        /// an empty line, each one to be submitted in its own block
+        #[arg(long, conflicts_with = "replace")]
+        max_scheduled_per_block: Option<u32>,
+        /// Format of the file, guessed from its extension when not given
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+}
+
+fn run(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Validate { file, format } => {
+            let styles = read_taxonomy(&file, format)?;
+            let subs: usize = styles.values().map(|subs| subs.len()).sum();
+            println!(
+                "{} is valid: {} parent styles, {} sub styles",
+                file.display(),
+                styles.len(),
+                subs
+            );
+        }
+        Command::Diff {
+            file,
+            state,
+            format,
+        } => {
+            let taxonomy = read_taxonomy(&file, format)?;
+            for change in diff(&read_state(&state)?, &taxonomy) {
+                println!("{}", describe(&change));
+            }
+        }
+        Command::Calls {
+            file,
+            state,
+            pallet_index,
+            reason,
+            replace,
+            max_scheduled_per_block,
+            format,
+        } => {
+            let taxonomy = read_taxonomy(&file, format)?;
+            let reason = to_reason(reason)?;
+            if replace {
+                let call = replace_call(taxonomy, reason);
+                println!("{}", to_hex(&encode_call(pallet_index, &call)));
+                return Ok(());
+            }
+
+            let current = match state {
+                Some(state) => read_state(&state)?,
+                None => MusicStyleDB::new(),
+            };
+            let calls = to_calls(&diff(&current, &taxonomy), reason);
+            let batches = match max_scheduled_per_block {
+                Some(max_scheduled) => to_batches(calls, max_scheduled),
+                None => {
+                    let scheduled = calls.iter().filter(|call| is_scheduled(call)).count();
+                    if scheduled > 1 {
+                        eprintln!(
+                            "warning: {} changes are scheduled, give --max-scheduled-per-block to \
+                             split them across blocks if they exceed the agenda of the runtime",
+                            scheduled
+                        );
+                    }
+                    vec![calls]
+                }
+            };
+
+            for (i, batch) in batches.into_iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                for call in batch {
+                    println!("{}", to_hex(&encode_call(pallet_index, &call)));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+//! Runtime only used to reach the pallet types offline: its parameters don't change the encoding
+//! of the calls, and the pallet index is given on the command line
+
+use frame_support::{
+    construct_runtime,
+    traits::{ConstU32, ConstU64},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+construct_runtime!(
+    pub enum Runtime
+    {
+        System: frame_system::{Pallet, Call, Storage, Event<T>},
+        MusicStyles: pallet_music_styles::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+impl frame_system::Config for Runtime {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type Nonce = u64;
+    type RuntimeCall = RuntimeCall;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_music_styles::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type AdminOrigin = EnsureRoot<u64>;
+    type PauseOrigin = EnsureRoot<u64>;
+    type TaxonomyOrigin = EnsureRoot<u64>;
+    type OnStyleChange = ();
+    type EnactmentDelay = ConstU64<1>;
+    type MaxScheduledPerBlock = ConstU32<1>;
+    type RatePeriod = ConstU64<1>;
+    type MaxNewParentStylesPerPeriod = ConstU32<{ u32::MAX }>;
+    type MaxNewSubStylesPerPeriod = ConstU32<{ u32::MAX }>;
+    type MaxHistory = ConstU32<0>;
+    type MaxChangeLogEntries = ConstU32<0>;
+    type Weights = ();
+}
\ No newline at end of file
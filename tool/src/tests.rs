@@ -0,0 +1,286 @@
+use super::*;
+use codec::Decode;
+
+fn bounded(name: &str) -> MusicStyleName {
+    name.as_bytes().to_vec().try_into().unwrap()
+}
+
+fn tree(styles: &[(&str, &[&str])]) -> MusicStyleDB {
+    let mut tree = MusicStyleDB::default();
+    for (parent, subs) in styles {
+        let subs = subs.iter().map(|sub| bounded(sub)).collect::<Vec<_>>();
+        tree.try_insert(bounded(parent), subs.try_into().unwrap())
+            .unwrap();
+    }
+    tree
+}
+
+/// Genesis styles of the pallet mock: Raggae [], Rap [Drill, Trap, Hardcore], Rock [Hardcore]
+fn current() -> MusicStyleDB {
+    tree(&[
+        ("Raggae", &[]),
+        ("Rap", &["Drill", "Trap", "Hardcore"]),
+        ("Rock", &["Hardcore"]),
+    ])
+}
+
+#[test]
+fn taxonomy_format_is_guessed_from_the_extension() {
+    assert_eq!(Format::of(Path::new("styles.CSV")), Format::Csv);
+    assert_eq!(Format::of(Path::new("styles.json")), Format::Json);
+    assert_eq!(Format::of(Path::new("styles")), Format::Json);
+}
+
+#[test]
+fn invalid_taxonomy_is_reported_with_its_line() {
+    let error = parse_taxonomy("Rap,Drill\nRap,Drill\n", Format::Csv).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "invalid taxonomy, line 2: Drill is listed twice under Rap"
+    );
+}
+
+#[test]
+fn state_is_decoded_from_raw_or_hex_dumps() {
+    let encoded = current().encode();
+
+    assert_eq!(decode_state(&encoded).unwrap(), current());
+    assert_eq!(
+        decode_state(format!("{}\n", to_hex(&encoded)).as_bytes()).unwrap(),
+        current()
+    );
+    assert!(matches!(decode_state(&encoded[1..]), Err(Error::State(_))));
+}
+
+fn parent(name: &str) -> StyleRef {
+    StyleRef::Parent(bounded(name))
+}
+
+fn sub(parent: &str, name: &str) -> StyleRef {
+    StyleRef::Sub(bounded(parent), bounded(name))
+}
+
+#[test]
+fn taxonomy_is_diffed_against_the_current_styles() {
+    let taxonomy = parse_taxonomy(
+        "parent,sub\nRap,Drill\nRap,Trap\nRap,Grime\nRock,Hardcore\nJazz,Bebop\n",
+        Format::Csv,
+    )
+    .unwrap();
+
+    let changes = diff(&current(), &taxonomy);
+    assert_eq!(
+        changes,
+        vec![
+            StyleChange::Remove {
+                style: parent("Raggae"),
+                force: false
+            },
+            StyleChange::Remove {
+                style: sub("Rap", "Hardcore"),
+                force: false
+            },
+            StyleChange::Add {
+                style: parent("Jazz")
+            },
+            StyleChange::Add {
+                style: sub("Jazz", "Bebop")
+            },
+            StyleChange::Add {
+                style: sub("Rap", "Grime")
+            },
+        ]
+    );
+    assert!(diff(&current(), &current()).is_empty());
+}
+
+#[test]
+fn changes_are_described() {
+    let described: Vec<String> = [
+        StyleChange::Add {
+            style: parent("Jazz"),
+        },
+        StyleChange::Remove {
+            style: sub("Rap", "Trap"),
+            force: false,
+        },
+        StyleChange::Rename {
+            style: parent("Raggae"),
+            new_name: bounded("Reggae"),
+        },
+        StyleChange::Move {
+            style: sub("Rap", "Drill"),
+            new_parent: bounded("Rock"),
+        },
+    ]
+    .iter()
+    .map(describe)
+    .collect();
+
+    assert_eq!(
+        described,
+        vec![
+            "+ Jazz",
+            "- Rap / Trap",
+            "~ Raggae -> Reggae",
+            "> Rap / Drill -> Rock"
+        ]
+    );
+}
+
+#[test]
+fn calls_name_the_styles_before_the_changes() {
+    let taxonomy = tree(&[
+        ("Reggae", &["Drill"]),
+        ("Rock", &["Hardcore"]),
+        ("Urban", &["Trap", "Hardcore", "Grime"]),
+    ]);
+    let changes = diff(&current(), &taxonomy);
+    assert_eq!(
+        changes,
+        vec![
+            StyleChange::Rename {
+                style: parent("Raggae"),
+                new_name: bounded("Reggae")
+            },
+            StyleChange::Rename {
+                style: parent("Rap"),
+                new_name: bounded("Urban")
+            },
+            StyleChange::Move {
+                style: sub("Urban", "Drill"),
+                new_parent: bounded("Reggae")
+            },
+            StyleChange::Add {
+                style: sub("Urban", "Grime")
+            },
+        ]
+    );
+
+    assert_eq!(
+        to_calls(&changes, None),
+        vec![
+            Call::add_sub_style {
+                parent_style: b"Raggae".to_vec(),
+                subs_style: vec![b"Drill".to_vec()],
+                reason: None,
+            },
+            Call::add_sub_style {
+                parent_style: b"Rap".to_vec(),
+                subs_style: vec![b"Grime".to_vec()],
+                reason: None,
+            },
+            Call::remove_style {
+                parent_style: b"Rap".to_vec(),
+                sub_style: Some(b"Drill".to_vec()),
+                reason: None,
+            },
+            Call::update_style_name {
+                parent_style: b"Raggae".to_vec(),
+                sub_style: None,
+                new_name: b"Reggae".to_vec(),
+                reason: None,
+            },
+            Call::update_style_name {
+                parent_style: b"Rap".to_vec(),
+                sub_style: None,
+                new_name: b"Urban".to_vec(),
+                reason: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn scheduled_calls_are_split_across_blocks() {
+    let changes = [
+        StyleChange::Add {
+            style: parent("Jazz"),
+        },
+        StyleChange::Remove {
+            style: sub("Rap", "Drill"),
+            force: false,
+        },
+        StyleChange::Remove {
+            style: sub("Rap", "Trap"),
+            force: false,
+        },
+        StyleChange::Rename {
+            style: sub("Rock", "Hardcore"),
+            new_name: bounded("Metal"),
+        },
+        StyleChange::Remove {
+            style: parent("Raggae"),
+            force: false,
+        },
+    ];
+    let calls = to_calls(&changes, None);
+    assert_eq!(calls.iter().filter(|call| is_scheduled(call)).count(), 4);
+
+    let batches = to_batches(calls.clone(), 2);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].len(), 3);
+    assert!(batches
+        .iter()
+        .all(|batch| batch.iter().filter(|call| is_scheduled(call)).count() <= 2));
+    assert_eq!(batches.concat(), calls);
+    assert!(!is_scheduled(&batches[0][0]));
+
+    assert_eq!(to_batches(calls.clone(), 4), vec![calls.clone()]);
+    assert_eq!(to_batches(calls, 0).len(), 4);
+}
+
+#[test]
+fn calls_are_encoded_for_the_pallet_index() {
+    let reason = to_reason(Some("Yearly review".into())).unwrap();
+    let calls = to_calls(
+        &diff(&MusicStyleDB::new(), &tree(&[("Jazz", &["Bebop"])])),
+        reason.clone(),
+    );
+    let call_data = encode_call(42, &calls[0]);
+
+    assert_eq!(&call_data[..2], &[42, 0]);
+    assert_eq!(Call::decode(&mut &call_data[1..]).unwrap(), calls[0]);
+    assert_eq!(
+        calls,
+        vec![Call::add_style {
+            name: b"Jazz".to_vec(),
+            sub: Some(vec![b"Bebop".to_vec()]),
+            reason,
+        }]
+    );
+
+    let removal = to_calls(
+        &[StyleChange::Remove {
+            style: sub("Rap", "Drill"),
+            force: false,
+        }],
+        None,
+    );
+    assert_eq!(encode_call(42, &removal[0])[..2], [42, 3]);
+    assert!(to_hex(&encode_call(42, &removal[0])).starts_with("0x2a03"));
+}
+
+#[test]
+fn taxonomy_is_replaced_in_one_call() {
+    let call = replace_call(current(), None);
+
+    assert_eq!(encode_call(42, &call)[..2], [42, 12]);
+    assert_eq!(
+        call,
+        Call::set_taxonomy {
+            new: current(),
+            reason: None
+        }
+    );
+}
+
+#[test]
+fn reasons_are_bounded() {
+    assert!(matches!(
+        to_reason(Some("a".repeat(257))),
+        Err(Error::ReasonTooLong)
+    ));
+    assert_eq!(to_reason(None).unwrap(), None);
+}
\ No newline at end of file